@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+use bevy_reactor_signals::{
+    Callback, CallbackOwner, CreateDerived, CreateMutable, Mutable, Rcx, ReactionCell, SetOwner,
+    Signal, TrackingScope,
+};
+
+use crate::{ui_builder::MemoReaction, UiBuilder};
+
+/// Builder which queues the construction of reactive UI using [`Commands`], so that it can be
+/// used from ordinary systems rather than exclusive ones. Handles for the entities, mutables
+/// and callbacks it creates are returned immediately, but the objects themselves are not
+/// created until the commands are applied.
+pub struct UiCommands<'a, 'w, 's> {
+    commands: &'a mut Commands<'w, 's>,
+    parent: Entity,
+}
+
+impl<'a, 'w, 's> UiCommands<'a, 'w, 's> {
+    /// Construct a new `UiCommands` which adds children to `parent`.
+    pub fn new(commands: &'a mut Commands<'w, 's>, parent: Entity) -> Self {
+        Self { commands, parent }
+    }
+
+    /// The entity which children are added to.
+    pub fn parent(&self) -> Entity {
+        self.parent
+    }
+
+    /// Access the underlying [`Commands`].
+    pub fn commands(&mut self) -> &mut Commands<'w, 's> {
+        self.commands
+    }
+
+    /// Spawn a new child of the parent entity.
+    pub fn spawn(&mut self, bundle: impl Bundle) -> Entity {
+        let mut entity = self.commands.spawn(bundle);
+        entity.set_parent(self.parent);
+        entity.id()
+    }
+
+    /// Spawn a new, empty child of the parent entity.
+    pub fn spawn_empty(&mut self) -> Entity {
+        let mut entity = self.commands.spawn_empty();
+        entity.set_parent(self.parent);
+        entity.id()
+    }
+
+    /// Create a new [`Mutable`] which is owned by the parent entity.
+    #[track_caller]
+    pub fn create_mutable<T: Send + Sync + 'static>(&mut self, init: T) -> Mutable<T> {
+        let mutable = self.commands.create_mutable(init);
+        self.commands.entity(mutable.id()).set_owner(self.parent);
+        mutable
+    }
+
+    /// Create a new derived signal which is owned by the parent entity. See
+    /// [`UiBuilder::create_derived`].
+    #[track_caller]
+    pub fn create_derived<R: 'static, F: Send + Sync + 'static + Fn(&mut Rcx) -> R>(
+        &mut self,
+        compute: F,
+    ) -> Signal<R> {
+        let derived = self.commands.create_derived(compute);
+        self.commands.entity(derived.id()).set_owner(self.parent);
+        Signal::Derived(derived)
+    }
+
+    /// Create a new memoized computation which is owned by the parent entity. See
+    /// [`UiBuilder::create_memo`]. The initial value is computed when the commands are applied.
+    #[track_caller]
+    pub fn create_memo<
+        R: 'static + PartialEq + Send + Sync + Clone,
+        F: Send + Sync + 'static + Fn(&mut Rcx) -> R,
+    >(
+        &mut self,
+        compute: F,
+    ) -> Signal<R> {
+        let owner = self.parent;
+        let mutable = self
+            .commands
+            .create_mutable_with(move |entity: &mut EntityWorldMut| {
+                let mut scope = TrackingScope::new(entity.world_scope(|world| world.change_tick()));
+                let init =
+                    entity.world_scope(|world| compute(&mut Rcx::new(world, owner, &mut scope)));
+                entity.insert((
+                    ReactionCell::new(MemoReaction(compute)),
+                    scope,
+                    Name::new(format!("Memo::<{}>", std::any::type_name::<R>())),
+                ));
+                init
+            });
+        self.commands.entity(mutable.id()).set_owner(owner);
+        mutable.signal()
+    }
+
+    /// Create a new callback which is owned by the parent entity.
+    pub fn create_callback<
+        P: Send + 'static,
+        R: Send + 'static,
+        M,
+        S: IntoSystem<In<P>, R, M> + 'static,
+    >(
+        &mut self,
+        callback: S,
+    ) -> Callback<P, R> {
+        let result = Callback::new(self.commands.register_system(callback));
+        let parent = self.parent;
+        self.commands.queue(move |world: &mut World| {
+            match world.get_mut::<CallbackOwner>(parent) {
+                Some(mut owner) => {
+                    owner.add(result);
+                }
+                None => {
+                    let mut owner = CallbackOwner::new();
+                    owner.add(result);
+                    world.entity_mut(parent).insert(owner);
+                }
+            }
+        });
+        result
+    }
+
+    /// Queue a function which modifies `entity` once the commands are applied. This allows
+    /// the extension traits for [`EntityWorldMut`], such as
+    /// [`EntityStyleBuilder`](crate::EntityStyleBuilder), to be used.
+    pub fn entity<F: FnOnce(EntityWorldMut) + Send + 'static>(
+        &mut self,
+        entity: Entity,
+        f: F,
+    ) -> &mut Self {
+        self.commands.entity(entity).queue(f);
+        self
+    }
+
+    /// Queue a function which builds children of the parent entity using a [`UiBuilder`] once
+    /// the commands are applied.
+    pub fn build<F: FnOnce(&mut UiBuilder) + Send + 'static>(&mut self, f: F) -> &mut Self {
+        let parent = self.parent;
+        self.commands.queue(move |world: &mut World| {
+            f(&mut UiBuilder::new(world, parent));
+        });
+        self
+    }
+
+    /// Build children of `entity`, which is usually a child of the parent entity.
+    pub fn create_children<R>(
+        &mut self,
+        entity: Entity,
+        f: impl FnOnce(&mut UiCommands) -> R,
+    ) -> R {
+        f(&mut UiCommands::new(self.commands, entity))
+    }
+}
+
+/// Extension trait which allows reactive UI to be built from [`Commands`].
+pub trait BuildUiCommands {
+    /// Build reactive UI as children of `parent`. The closure is called immediately, and can
+    /// return the handles it creates; the UI itself is built when the commands are applied.
+    fn build_ui<R>(&mut self, parent: Entity, f: impl FnOnce(&mut UiCommands) -> R) -> R;
+}
+
+impl<'w, 's> BuildUiCommands for Commands<'w, 's> {
+    fn build_ui<R>(&mut self, parent: Entity, f: impl FnOnce(&mut UiCommands) -> R) -> R {
+        f(&mut UiCommands::new(self, parent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reactor_signals::{RunCallback, SignalsPlugin};
+
+    use super::*;
+    use crate::{EntityEffectBuilder, TextBuilder};
+
+    #[derive(Resource, Clone, Copy)]
+    struct Handles(Mutable<i32>, Callback, Entity, Signal<i32>, Signal<bool>);
+
+    #[derive(Component, PartialEq, Debug)]
+    struct Count(i32);
+
+    fn setup(mut commands: Commands) {
+        let root = commands.spawn_empty().id();
+        let handles = commands.build_ui(root, |ui| {
+            let count = ui.create_mutable(1);
+            let increment = ui.create_callback(move |_: In<()>, mut world: Commands| {
+                world.queue(move |world: &mut World| {
+                    count.update(world, |mut value| *value += 1);
+                });
+            });
+            let doubled = ui.create_derived(move |rcx| count.get(rcx) * 2);
+            let even = ui.create_memo(move |rcx| count.get(rcx) % 2 == 0);
+            let label = ui.spawn_empty();
+            ui.entity(label, move |mut entity| {
+                entity.effect(
+                    move |rcx: &Rcx| count.get(rcx),
+                    |value, entity| {
+                        entity.insert(Count(value));
+                    },
+                );
+            });
+            ui.build(move |builder| {
+                builder.text_computed(move |rcx| count.get(rcx).to_string());
+            });
+            Handles(count, increment, label, doubled, even)
+        });
+        commands.insert_resource(handles);
+    }
+
+    #[test]
+    fn test_build_ui_commands() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin).add_systems(Startup, setup);
+        app.update();
+        let Handles(count, increment, label, doubled, even) = *app.world().resource::<Handles>();
+        assert_eq!(count.get(app.world()), 1);
+        assert_eq!(doubled.get(app.world()), 2);
+        assert!(!even.get(app.world()));
+        assert_eq!(app.world().get::<Count>(label), Some(&Count(1)));
+
+        app.world_mut().run_callback(increment, ());
+        app.world_mut().flush();
+        app.update();
+        assert_eq!(count.get(app.world()), 2);
+        assert_eq!(doubled.get(app.world()), 4);
+        assert!(even.get(app.world()));
+        assert_eq!(app.world().get::<Count>(label), Some(&Count(2)));
+    }
+}
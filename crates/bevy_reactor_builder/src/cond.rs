@@ -1,7 +1,9 @@
 use bevy::ecs::world::World;
 use bevy::prelude::*;
 use bevy::ui::experimental::GhostNode;
-use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, TrackingScope};
+use bevy_reactor_signals::{
+    DespawnOwned, Rcx, Reaction, ReactionCell, ReactionKind, TrackingScope,
+};
 
 use crate::test_condition::TestCondition;
 use crate::{CreateChilden, UiBuilder};
@@ -139,7 +141,10 @@ impl<
         owner: Entity,
         world: &mut World,
     ) {
-        world.entity_mut(owner).despawn_descendants();
+        world
+            .entity_mut(owner)
+            .despawn_descendants()
+            .despawn_owned();
         world.entity_mut(owner).create_children_mut(branch);
     }
 }
@@ -167,4 +172,8 @@ impl<
             self.state = cond;
         }
     }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Structural
+    }
 }
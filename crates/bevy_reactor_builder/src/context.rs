@@ -0,0 +1,94 @@
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::UiBuilder;
+
+/// Component which holds the context values provided by an entity to its descendants.
+#[derive(Component, Default)]
+pub struct Contexts(HashMap<TypeId, Arc<dyn Any + Send + Sync>>);
+
+pub trait ContextBuilder {
+    /// Provide a context value to the current parent entity and all of its descendants. The
+    /// value can be any type, including a [`Signal`](bevy_reactor_signals::Signal); a value
+    /// provided by a nearer ancestor overrides one of the same type provided further up.
+    fn provide_context<T: Send + Sync + 'static>(&mut self, value: T) -> &mut Self;
+
+    /// Return the nearest context value of type `T` provided by the current parent entity or
+    /// one of its ancestors. The value is looked up when this is called, and is not reactive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no ancestor has provided a value of type `T`.
+    fn use_context<T: Clone + Send + Sync + 'static>(&self) -> T;
+
+    /// Like [`use_context`](ContextBuilder::use_context), but returns `None` if no ancestor has
+    /// provided a value of type `T`.
+    fn try_use_context<T: Clone + Send + Sync + 'static>(&self) -> Option<T>;
+}
+
+impl<'w> ContextBuilder for UiBuilder<'w> {
+    fn provide_context<T: Send + Sync + 'static>(&mut self, value: T) -> &mut Self {
+        let parent = self.parent();
+        let mut entity = self.world_mut().entity_mut(parent);
+        if !entity.contains::<Contexts>() {
+            entity.insert(Contexts::default());
+        }
+        let mut contexts = entity.get_mut::<Contexts>().unwrap();
+        contexts.0.insert(TypeId::of::<T>(), Arc::new(value));
+        self
+    }
+
+    fn use_context<T: Clone + Send + Sync + 'static>(&self) -> T {
+        match self.try_use_context::<T>() {
+            Some(value) => value,
+            None => panic!(
+                "No context of type {} was provided for entity {} or any of its ancestors",
+                std::any::type_name::<T>(),
+                self.parent()
+            ),
+        }
+    }
+
+    fn try_use_context<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        let world = self.world();
+        let mut entity = self.parent();
+        loop {
+            if let Some(value) = world
+                .get::<Contexts>(entity)
+                .and_then(|contexts| contexts.0.get(&TypeId::of::<T>()))
+            {
+                return value.downcast_ref::<T>().cloned();
+            }
+            entity = **world.get::<Parent>(entity)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CreateChilden;
+
+    #[derive(Clone, PartialEq, Debug)]
+    struct Theme(&'static str);
+
+    #[test]
+    fn test_context() {
+        let mut world = World::default();
+        let root = world.spawn_empty().id();
+        let mut builder = UiBuilder::new(&mut world, root);
+        assert_eq!(builder.try_use_context::<Theme>(), None);
+        builder.provide_context(Theme("dark")).provide_context(7u32);
+        builder.spawn_empty().create_children(|builder| {
+            assert_eq!(builder.use_context::<Theme>(), Theme("dark"));
+            builder.provide_context(Theme("light"));
+            builder.spawn_empty().create_children(|builder| {
+                assert_eq!(builder.use_context::<Theme>(), Theme("light"));
+                assert_eq!(builder.use_context::<u32>(), 7);
+            });
+        });
+    }
+}
@@ -1,9 +1,8 @@
 use bevy::{
     core::Name,
-    prelude::{BuildChildren, Entity, EntityWorldMut, World},
-    ui::experimental::GhostNode,
+    prelude::{Entity, EntityWorldMut, World},
 };
-use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, TrackingScope};
+use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, SetOwner, TrackingScope};
 
 pub trait EntityEffectBuilder {
     fn effect<
@@ -36,13 +35,11 @@ impl<'w> EntityEffectBuilder for EntityWorldMut<'w> {
         let owner = self.id();
         self.world_scope(|world| {
             // Spawn a new reaction entity to contain the effect.
-            let effect_owner = world.spawn(Name::new("Effect")).set_parent(owner).id();
+            let effect_owner = world.spawn(Name::new("Effect")).set_owner(owner).id();
             reaction.apply(effect_owner, world, &mut scope);
-            world.entity_mut(effect_owner).insert((
-                scope,
-                ReactionCell::new(reaction),
-                GhostNode::default(),
-            ));
+            world
+                .entity_mut(effect_owner)
+                .insert((scope, ReactionCell::new(reaction)));
         });
         self
     }
@@ -3,7 +3,7 @@ use std::ops::Range;
 use bevy::ecs::world::World;
 use bevy::prelude::*;
 use bevy::ui::experimental::GhostNode;
-use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, TrackingScope};
+use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, ReactionKind, TrackingScope};
 
 use crate::lcs::lcs;
 use crate::UiBuilder;
@@ -317,4 +317,8 @@ impl<
             _ => {}
         }
     }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Structural
+    }
 }
@@ -0,0 +1,269 @@
+use bevy::ecs::world::World;
+use bevy::prelude::*;
+use bevy::ui::experimental::GhostNode;
+use bevy_reactor_signals::{
+    MutableVec, Rcx, Reaction, ReactionCell, ReactionKind, TrackingScope, VecOp,
+};
+
+use crate::UiBuilder;
+
+pub trait ForEachVecBuilder {
+    /// Construct child elements from a [`MutableVec`]. The callback is called once for each
+    /// item, and builds children for that item. Rather than comparing the old and new items,
+    /// the operations recorded by the vector are applied directly to the children: only items
+    /// which have been added or replaced are rebuilt, and moved items keep their children.
+    fn for_each_vec<
+        Item: Send + Sync + 'static + Clone,
+        EachFn: Send + Sync + 'static + Fn(&Item, &mut UiBuilder),
+        FallbackFn: Send + Sync + 'static + Fn(&mut UiBuilder),
+    >(
+        &mut self,
+        items: MutableVec<Item>,
+        each: EachFn,
+        fallback: FallbackFn,
+    ) -> &mut Self;
+}
+
+impl<'w> ForEachVecBuilder for UiBuilder<'w> {
+    fn for_each_vec<
+        Item: Send + Sync + 'static + Clone,
+        EachFn: Send + Sync + 'static + Fn(&Item, &mut UiBuilder),
+        FallbackFn: Send + Sync + 'static + Fn(&mut UiBuilder),
+    >(
+        &mut self,
+        items: MutableVec<Item>,
+        each: EachFn,
+        fallback: FallbackFn,
+    ) -> &mut Self {
+        let mut owner = self.spawn(Name::new("ForEachVec"));
+        let owner_id = owner.id();
+
+        // Create a tracking scope and reaction.
+        let mut tracking = TrackingScope::new(owner.world().last_change_tick());
+        let mut reaction = ForEachVecReaction {
+            items,
+            each,
+            fallback,
+            fallback_ent: None,
+            version: None,
+            children: Vec::new(),
+        };
+
+        // Safety: this should be safe because we don't use owner any more after this
+        // point.
+        let world = unsafe { owner.world_mut() };
+        // Trigger the initial reaction.
+        reaction.react(owner_id, world, &mut tracking);
+        world.entity_mut(owner_id).insert((
+            GhostNode::default(),
+            tracking,
+            ReactionCell::new(reaction),
+        ));
+        self
+    }
+}
+
+/// A reaction which applies the operations recorded by a [`MutableVec`] to a list of children.
+struct ForEachVecReaction<
+    Item: Send + Sync + 'static,
+    EachFn: Send + Sync + 'static + Fn(&Item, &mut UiBuilder),
+    FallbackFn: Send + Sync + 'static + Fn(&mut UiBuilder),
+> {
+    items: MutableVec<Item>,
+    each: EachFn,
+    fallback: FallbackFn,
+    fallback_ent: Option<Entity>,
+
+    /// The version of the vector which `children` reflects.
+    version: Option<u64>,
+
+    /// The child entity for each item, or `None` if the child needs to be built.
+    children: Vec<Option<Entity>>,
+}
+
+impl<
+        Item: Send + Sync + 'static + Clone,
+        EachFn: Send + Sync + 'static + Fn(&Item, &mut UiBuilder),
+        FallbackFn: Send + Sync + 'static + Fn(&mut UiBuilder),
+    > ForEachVecReaction<Item, EachFn, FallbackFn>
+{
+    /// Despawn all children, so that they are rebuilt.
+    fn reset(&mut self, world: &mut World, len: usize) {
+        for child in self.children.drain(..).flatten() {
+            world.entity_mut(child).despawn_recursive();
+        }
+        self.children.resize(len, None);
+    }
+
+    /// Apply an operation to the list of children. Children for new or replaced items are
+    /// not built until all operations have been applied, since the item may have been changed
+    /// again by a later operation.
+    fn apply(&mut self, world: &mut World, op: VecOp) {
+        let mut despawn = |child: Option<Entity>| {
+            if let Some(child) = child {
+                world.entity_mut(child).despawn_recursive();
+            }
+        };
+        match op {
+            VecOp::Push => self.children.push(None),
+            VecOp::Insert(index) => self.children.insert(index, None),
+            VecOp::Remove(index) => despawn(self.children.remove(index)),
+            VecOp::Move { from, to } => {
+                let child = self.children.remove(from);
+                self.children.insert(to, child);
+            }
+            VecOp::Set(index) => despawn(self.children[index].take()),
+            VecOp::Clear => {
+                for child in self.children.drain(..) {
+                    despawn(child);
+                }
+            }
+        }
+    }
+}
+
+impl<
+        Item: Send + Sync + 'static + Clone,
+        EachFn: Send + Sync + 'static + Fn(&Item, &mut UiBuilder),
+        FallbackFn: Send + Sync + 'static + Fn(&mut UiBuilder),
+    > Reaction for ForEachVecReaction<Item, EachFn, FallbackFn>
+{
+    fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+        let changes = self
+            .items
+            .changes_since(&Rcx::new(world, owner, tracking), self.version);
+        self.version = Some(changes.version);
+        match changes.ops {
+            Some(ops) => {
+                for op in ops {
+                    self.apply(world, op);
+                }
+            }
+            None => {
+                let len = self.items.len(world);
+                self.reset(world, len);
+            }
+        }
+
+        // Build children for new and replaced items.
+        for index in 0..self.children.len() {
+            if self.children[index].is_none() {
+                let item = self.items.get(world, index).unwrap();
+                let child_id = world.spawn(GhostNode::default()).id();
+                (self.each)(&item, &mut UiBuilder::new(world, child_id));
+                self.children[index] = Some(child_id);
+            }
+        }
+        let mut children: Vec<Entity> = self.children.iter().flatten().copied().collect();
+
+        // Handle fallback
+        match self.fallback_ent {
+            // If there are > 0 items, destroy fallback if present.
+            Some(fb_ent) if !children.is_empty() => {
+                world.entity_mut(fb_ent).despawn_recursive();
+                self.fallback_ent = None;
+            }
+
+            // If there are no items, render fallback unless already rendered.
+            None if children.is_empty() => {
+                let fallback_id = world.spawn(GhostNode::default()).id();
+                let mut builder = UiBuilder::new(world, fallback_id);
+                (self.fallback)(&mut builder);
+                self.fallback_ent = Some(fallback_id);
+            }
+
+            // Otherwise, no change.
+            _ => {}
+        }
+
+        // The fallback, if present, is the only child.
+        children.extend(self.fallback_ent);
+        world.entity_mut(owner).replace_children(&children);
+    }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Structural
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reactor_signals::{create_mutable_vec, SignalsPlugin};
+
+    use super::*;
+
+    #[derive(Component)]
+    struct Label(char);
+
+    fn labels(world: &World, owner: Entity) -> Vec<(Entity, char)> {
+        world
+            .get::<Children>(owner)
+            .unwrap()
+            .iter()
+            .map(|child| {
+                let label = world.get::<Children>(*child).unwrap()[0];
+                (*child, world.get::<Label>(label).unwrap().0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_for_each_vec() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin);
+        let world = app.world_mut();
+        let root = world.spawn_empty().id();
+        let list = create_mutable_vec(world, root, vec!['a', 'b', 'c']);
+        UiBuilder::new(world, root).for_each_vec(
+            list,
+            |item, builder| {
+                builder.spawn(Label(*item));
+            },
+            |builder| {
+                builder.spawn(Label('-'));
+            },
+        );
+        // The list's mutable is owned by the root rather than being one of its children.
+        let owner = world.get::<Children>(root).unwrap()[0];
+        let before = labels(world, owner);
+        assert_eq!(before.iter().map(|(_, c)| *c).collect::<String>(), "abc");
+
+        app.update();
+
+        // Moved items keep their children; only new and replaced items are built.
+        list.move_item(app.world_mut(), 0, 2);
+        list.push(app.world_mut(), 'd');
+        list.set(app.world_mut(), 0, 'x');
+        list.remove(app.world_mut(), 1);
+        app.update();
+        let after = labels(app.world(), owner);
+        assert_eq!(after.iter().map(|(_, c)| *c).collect::<String>(), "xad");
+        assert_eq!(after[1].0, before[0].0);
+        assert!(app.world().get_entity(before[1].0).is_err());
+        assert!(app.world().get_entity(before[2].0).is_err());
+
+        list.clear(app.world_mut());
+        app.update();
+        let fallback = app.world().get::<Children>(owner).unwrap()[0];
+        assert_eq!(
+            app.world()
+                .get::<Label>(app.world().get::<Children>(fallback).unwrap()[0])
+                .unwrap()
+                .0,
+            '-'
+        );
+
+        // A second pass with no items keeps the existing fallback.
+        list.push(app.world_mut(), 'e');
+        list.remove(app.world_mut(), 0);
+        app.update();
+        assert_eq!(app.world().get::<Children>(owner).unwrap()[..], [fallback]);
+
+        // The fallback is removed once there are items again.
+        list.push(app.world_mut(), 'f');
+        app.update();
+        let after = labels(app.world(), owner);
+        assert_eq!(after.iter().map(|(_, c)| *c).collect::<String>(), "f");
+        assert!(app.world().get_entity(fallback).is_err());
+    }
+}
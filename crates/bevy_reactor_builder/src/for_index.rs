@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 use bevy::{ecs::world::World, ui::experimental::GhostNode};
-use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, TrackingScope};
+use bevy_reactor_signals::{
+    DespawnOwned, Rcx, Reaction, ReactionCell, ReactionKind, TrackingScope,
+};
 
 use crate::UiBuilder;
 
@@ -111,7 +113,10 @@ impl<
                 // Overwrite existing items.
                 let entry = &mut self.state[index];
                 if item != entry.item {
-                    world.entity_mut(entry.child).despawn_descendants();
+                    world
+                        .entity_mut(entry.child)
+                        .despawn_descendants()
+                        .despawn_owned();
                     (self.each)(&item, index, &mut UiBuilder::new(world, entry.child));
                     entry.item = item.clone();
                 }
@@ -161,4 +166,8 @@ impl<
             _ => {}
         }
     }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Structural
+    }
 }
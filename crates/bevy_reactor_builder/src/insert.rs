@@ -1,5 +1,5 @@
-use bevy::{prelude::*, ui::experimental::GhostNode};
-use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, TrackingScope};
+use bevy::prelude::*;
+use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, SetOwner, TrackingScope};
 
 use crate::test_condition::TestCondition;
 
@@ -29,13 +29,11 @@ impl<'w> InsertComponentBuilder for EntityWorldMut<'w> {
         let owner = self.id();
         self.world_scope(|world| {
             // Spawn a new reaction entity to contain the effect.
-            let effect_owner = world.spawn_empty().set_parent(owner).id();
+            let effect_owner = world.spawn_empty().set_owner(owner).id();
             reaction.react(effect_owner, world, &mut scope);
-            world.entity_mut(effect_owner).insert((
-                scope,
-                ReactionCell::new(reaction),
-                GhostNode::default(),
-            ));
+            world
+                .entity_mut(effect_owner)
+                .insert((scope, ReactionCell::new(reaction)));
         });
         self
     }
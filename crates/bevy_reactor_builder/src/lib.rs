@@ -1,19 +1,28 @@
+mod commands;
 mod cond;
+mod context;
 mod effect;
 mod for_each;
+mod for_each_vec;
 mod for_index;
 mod insert;
 mod lcs;
 mod style;
 mod switch;
 mod test_condition;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 mod text;
 mod ui_builder;
 mod ui_template;
+mod watch;
 
+pub use commands::{BuildUiCommands, UiCommands};
 pub use cond::CondBuilder;
+pub use context::{ContextBuilder, Contexts};
 pub use effect::EntityEffectBuilder;
 pub use for_each::ForEachBuilder;
+pub use for_each_vec::ForEachVecBuilder;
 pub use for_index::ForIndexBuilder;
 pub use insert::InsertComponentBuilder;
 pub use style::EntityStyleBuilder;
@@ -21,3 +30,4 @@ pub use switch::SwitchBuilder;
 pub use text::TextBuilder;
 pub use ui_builder::{CreateChilden, UiBuilder};
 pub use ui_template::{InvokeUiTemplate, UiTemplate};
+pub use watch::{WatchBuilder, WatchSource};
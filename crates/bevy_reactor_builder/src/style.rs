@@ -1,9 +1,9 @@
 use bevy::{
-    prelude::{BuildChildren, Entity, EntityWorldMut, World},
-    ui::{self, experimental::GhostNode},
+    prelude::{Entity, EntityWorldMut, World},
+    ui,
 };
 use bevy_mod_stylebuilder::{StyleBuilder, StyleTuple};
-use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, TrackingScope};
+use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, SetOwner, TrackingScope};
 
 pub trait EntityStyleBuilder {
     fn style<S: FnOnce(&mut StyleBuilder)>(&mut self, style: S) -> &mut Self;
@@ -60,13 +60,11 @@ impl<'w> EntityStyleBuilder for EntityWorldMut<'w> {
         let owner = self.id();
         self.world_scope(|world| {
             // Spawn a new reaction entity to contain the effect.
-            let effect_owner = world.spawn_empty().set_parent(owner).id();
+            let effect_owner = world.spawn_empty().set_owner(owner).id();
             reaction.apply(effect_owner, world, &mut scope);
-            world.entity_mut(effect_owner).insert((
-                scope,
-                ReactionCell::new(reaction),
-                GhostNode::default(),
-            ));
+            world
+                .entity_mut(effect_owner)
+                .insert((scope, ReactionCell::new(reaction)));
         });
         self
     }
@@ -1,9 +1,11 @@
 #![allow(clippy::type_complexity)]
 
-use bevy::prelude::{BuildChildren, DespawnRecursiveExt, Entity};
+use bevy::prelude::{BuildChildren, DespawnRecursiveExt, Entity, States};
 use bevy::ui::experimental::GhostNode;
 use bevy::{core::Name, ecs::world::World};
-use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, Signal, TrackingScope};
+use bevy_reactor_signals::{
+    DespawnOwned, Rcx, Reaction, ReactionCell, ReactionKind, Signal, TrackingScope,
+};
 
 use crate::{CreateChilden, UiBuilder};
 
@@ -41,6 +43,10 @@ pub trait SwitchBuilder {
         value_fn: VF,
         cases_fn: CF,
     ) -> &mut Self;
+
+    /// Build a switch whose test value is the current value of the Bevy state `S`. Only the
+    /// children of the selected case are rebuilt when the state changes.
+    fn switch_state<S: States, CF: Fn(&mut CaseBuilder<S>)>(&mut self, cases_fn: CF) -> &mut Self;
 }
 
 impl<'w> SwitchBuilder for UiBuilder<'w> {
@@ -87,6 +93,10 @@ impl<'w> SwitchBuilder for UiBuilder<'w> {
             .insert((tracking, ReactionCell::new(reaction)));
         self
     }
+
+    fn switch_state<S: States, CF: Fn(&mut CaseBuilder<S>)>(&mut self, cases_fn: CF) -> &mut Self {
+        self.switch(|rcx: &Rcx| rcx.read_state::<S>(), cases_fn)
+    }
 }
 
 pub struct CaseBuilder<'a, Value: Send + Sync> {
@@ -140,7 +150,10 @@ impl<Value: Send + Sync + PartialEq, F: SwitchTestValue<Value>> Reaction
 
         if index != self.switch_index {
             self.switch_index = index;
-            world.entity_mut(owner).despawn_descendants();
+            world
+                .entity_mut(owner)
+                .despawn_descendants()
+                .despawn_owned();
             if index < self.cases.len() {
                 world
                     .entity_mut(owner)
@@ -150,4 +163,66 @@ impl<Value: Send + Sync + PartialEq, F: SwitchTestValue<Value>> Reaction
             };
         }
     }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Structural
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::state::app::StatesPlugin;
+    use bevy_reactor_signals::{RunCallback, SignalsPlugin};
+
+    use super::*;
+
+    #[derive(States, Clone, PartialEq, Eq, Hash, Debug, Default)]
+    enum Mode {
+        #[default]
+        Edit,
+        Play,
+        Pause,
+    }
+
+    #[derive(Component)]
+    struct Branch(&'static str);
+
+    #[test]
+    fn test_switch_state() {
+        let mut app = App::new();
+        app.add_plugins((StatesPlugin, SignalsPlugin))
+            .init_state::<Mode>();
+        let world = app.world_mut();
+        let root = world.spawn_empty().id();
+        let mut builder = UiBuilder::new(world, root);
+        let set_mode = builder.create_next_state_callback::<Mode>();
+        builder.switch_state::<Mode, _>(|cases| {
+            cases
+                .case(Mode::Edit, |builder| {
+                    builder.spawn(Branch("edit"));
+                })
+                .fallback(|builder| {
+                    builder.spawn(Branch("running"));
+                });
+        });
+        let switch = world.get::<Children>(root).unwrap()[0];
+        let branch = |world: &World| {
+            let child = world.get::<Children>(switch).unwrap()[0];
+            (child, world.get::<Branch>(child).unwrap().0)
+        };
+        let (edit, name) = branch(world);
+        assert_eq!(name, "edit");
+
+        world.run_callback(set_mode, Mode::Play);
+        app.update();
+        let (play, name) = branch(app.world());
+        assert_eq!(name, "running");
+        assert!(app.world().get_entity(edit).is_err());
+
+        // Switching between states which select the same case doesn't rebuild it.
+        app.world_mut().run_callback(set_mode, Mode::Pause);
+        app.update();
+        assert_eq!(branch(app.world()).0, play);
+    }
 }
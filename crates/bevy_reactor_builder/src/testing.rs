@@ -0,0 +1,170 @@
+//! Support for testing that reactive UI cleans up after itself.
+
+use bevy::{prelude::*, utils::HashSet};
+use bevy_reactor_signals::SignalsPlugin;
+
+use crate::{CreateChilden, InvokeUiTemplate, UiTemplate};
+
+/// Build `template` as the child of a new root entity, run the app for a few frames, then
+/// despawn the root. Returns the entities which were spawned in the meantime and outlived the
+/// root, such as reactive objects or callbacks which weren't owned by anything in the
+/// template. Entities which existed before the template was built are never included, even if
+/// the template despawned them.
+pub fn find_leaks<T: UiTemplate>(app: &mut App, template: T) -> Vec<Entity> {
+    if !app.is_plugin_added::<SignalsPlugin>() {
+        app.add_plugins(SignalsPlugin);
+    }
+    app.update();
+    let before: HashSet<Entity> = app.world().iter_entities().map(|e| e.id()).collect();
+
+    let root = app
+        .world_mut()
+        .spawn(Name::new("LeakTestRoot"))
+        .create_children(|builder| {
+            builder.invoke(template);
+        })
+        .id();
+    app.update();
+    app.update();
+    app.world_mut().entity_mut(root).despawn_recursive();
+    app.update();
+
+    let mut leaks: Vec<Entity> = app
+        .world()
+        .iter_entities()
+        .map(|e| e.id())
+        .filter(|entity| !before.contains(entity))
+        .collect();
+    leaks.sort();
+    leaks
+}
+
+/// Build and despawn `template` using [`find_leaks`], and panic if any of the entities it
+/// spawned, such as tracking scopes, reactions, mutables, deriveds or callbacks, are still
+/// alive.
+pub fn assert_no_leaks<T: UiTemplate>(app: &mut App, template: T) {
+    let leaks = find_leaks(app, template);
+    let world = app.world();
+    assert!(
+        leaks.is_empty(),
+        "Template leaked {} entities:\n{}",
+        leaks.len(),
+        leaks
+            .iter()
+            .map(|entity| describe_entity(world, *entity))
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+/// Describe an entity by listing its components.
+fn describe_entity(world: &World, entity: Entity) -> String {
+    let components: Vec<&str> = world
+        .entity(entity)
+        .archetype()
+        .components()
+        .filter_map(|id| world.components().get_name(id))
+        .collect();
+    format!("{:?}: {}", entity, components.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reactor_signals::{CreateMutable, RunCallback};
+
+    use super::*;
+    use crate::{
+        CondBuilder, EntityEffectBuilder, ForEachBuilder, InsertComponentBuilder, TextBuilder,
+        UiBuilder,
+    };
+
+    #[derive(Component)]
+    struct Marker;
+
+    struct Panel;
+
+    impl UiTemplate for Panel {
+        fn build(&self, builder: &mut UiBuilder) {
+            let count = builder.create_mutable(2);
+            let doubled = builder.create_derived(move |rcx| count.get(rcx) * 2);
+            let cached = builder.create_cached_derived(move |rcx| count.get(rcx) + 1);
+            let memo = builder.create_memo(move |rcx| doubled.get(rcx) > 2);
+            let increment = builder.create_callback(move |_: In<()>, mut commands: Commands| {
+                commands.queue(move |world: &mut World| {
+                    count.update(world, |mut value| *value += 1);
+                });
+            });
+            builder.create_effect(move |ecx| {
+                let value = cached.get(ecx);
+                if value > 10 {
+                    ecx.world_mut().run_callback(increment, ());
+                }
+            });
+            builder
+                .spawn(Name::new("Label"))
+                .effect(move |rcx| count.get(rcx), |_, _| {})
+                .insert_if(memo, || Marker);
+            builder.cond(
+                memo,
+                move |builder| {
+                    builder.create_mutable("inner");
+                    builder.text_computed(move |rcx| doubled.get(rcx).to_string());
+                },
+                |_| {},
+            );
+            builder.for_each(
+                move |rcx| 0..count.get(rcx),
+                |index, builder| {
+                    builder.create_mutable(*index);
+                    builder.text(index.to_string());
+                },
+                |_| {},
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_leaks() {
+        assert_no_leaks(&mut App::new(), Panel);
+    }
+
+    struct Leaky;
+
+    impl UiTemplate for Leaky {
+        fn build(&self, builder: &mut UiBuilder) {
+            // This mutable has no owner, so it survives the template.
+            builder.world_mut().create_mutable(0);
+        }
+    }
+
+    #[test]
+    fn test_detects_leaks() {
+        let leaks = find_leaks(&mut App::new(), Leaky);
+        assert_eq!(leaks.len(), 1);
+    }
+
+    /// Template which despawns an existing entity, as well as leaking a new one.
+    struct LeakyReplacement(Entity);
+
+    impl UiTemplate for LeakyReplacement {
+        fn build(&self, builder: &mut UiBuilder) {
+            builder.world_mut().despawn(self.0);
+            builder.world_mut().create_mutable(0);
+        }
+    }
+
+    #[test]
+    fn test_leaks_are_not_offset_by_frees() {
+        let mut app = App::new();
+        let existing = app.world_mut().create_mutable(0).id();
+        let leaks = find_leaks(&mut app, LeakyReplacement(existing));
+        assert_eq!(leaks.len(), 1);
+        assert_ne!(leaks[0], existing);
+    }
+
+    #[test]
+    #[should_panic(expected = "leaked")]
+    fn test_assert_no_leaks_panics() {
+        assert_no_leaks(&mut App::new(), Leaky);
+    }
+}
@@ -1,15 +1,27 @@
+use std::future::Future;
+
 use bevy::{
+    asset::{Asset, Handle, LoadState},
     core::Name,
+    ecs::query::{QueryFilter, ReadOnlyQueryData},
     prelude::{
-        BuildChildren, Bundle, Component, DespawnRecursiveExt, Entity, EntityWorldMut, In,
-        IntoSystem, Parent, World,
+        BuildChildren, Bundle, Component, DespawnRecursiveExt, Entity, EntityWorldMut, Event, In,
+        IntoSystem, NextState, Parent, ResMut, World,
     },
+    state::state::FreelyMutableState,
     ui::experimental::GhostNode,
+    utils::HashMap,
 };
 use bevy_reactor_signals::{
-    create_derived, create_mutable, Callback, CallbackOwner, Ecx, Mutable, Rcx, Reaction,
-    ReactionCell, Signal, TrackingScope, WriteMutable,
+    create_asset_state_signal, create_async_resource, create_cached_derived, create_debounced,
+    create_derived, create_event_signal, create_interval, create_mutable, create_mutable_map,
+    create_mutable_vec, create_mutable_with_history, create_persistent_mutable, create_query,
+    create_store, create_throttled, create_timeout, AsyncState, Callback, CallbackOwner,
+    DespawnOwned, Ecx, Mutable, MutableMap, MutableVec, MutableWithHistory, Rcx, Reaction,
+    ReactionCell, ReactionKind, ReactiveQuery, SetOwner, Signal, Store, TrackingScope,
+    WriteMutable,
 };
+use serde::{de::DeserializeOwned, Serialize};
 
 pub struct UiBuilder<'w> {
     /// Bevy World
@@ -64,10 +76,10 @@ impl<'w> UiBuilder<'w> {
     }
 
     /// Create a new callback which is owned by the parent entity.
-    pub fn create_callback<P: Send, M, S: IntoSystem<In<P>, (), M> + 'static>(
+    pub fn create_callback<P: Send, R: Send + 'static, M, S: IntoSystem<In<P>, R, M> + 'static>(
         &mut self,
         callback: S,
-    ) -> Callback<P> {
+    ) -> Callback<P, R> {
         let id = self.world_mut().register_system(callback);
         let result = Callback::new(id);
         let parent = self.parent();
@@ -84,7 +96,16 @@ impl<'w> UiBuilder<'w> {
         result
     }
 
+    /// Create a new callback, owned by the parent entity, which sets [`NextState<S>`] to the
+    /// state passed to it, causing a transition to that state.
+    pub fn create_next_state_callback<S: FreelyMutableState>(&mut self) -> Callback<S> {
+        self.create_callback(|In(state): In<S>, mut next: ResMut<NextState<S>>| {
+            next.set(state);
+        })
+    }
+
     /// Create a new [`Mutable`] in this context.
+    #[track_caller]
     pub fn create_mutable<T>(&mut self, init: T) -> Mutable<T>
     where
         T: Send + Sync + 'static,
@@ -92,6 +113,138 @@ impl<'w> UiBuilder<'w> {
         create_mutable(self.world, self.parent, init)
     }
 
+    /// Create a new persistent [`Mutable`] in this context. The value is restored from the
+    /// [`PersistentStorage`](bevy_reactor_signals::PersistentStorage) using the given key,
+    /// falling back to `default`, and is saved back to storage when it changes.
+    #[track_caller]
+    pub fn create_persistent_mutable<T>(&mut self, key: impl Into<String>, default: T) -> Mutable<T>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        create_persistent_mutable(self.world, self.parent, key, default)
+    }
+
+    /// Create a new [`MutableWithHistory`] in this context, which records up to `capacity`
+    /// prior values so that edits can be undone and redone.
+    #[track_caller]
+    pub fn create_mutable_with_history<T>(
+        &mut self,
+        init: T,
+        capacity: usize,
+    ) -> MutableWithHistory<T>
+    where
+        T: PartialEq + Clone + Send + Sync + 'static,
+    {
+        create_mutable_with_history(self.world, self.parent, init, capacity)
+    }
+
+    /// Create a signal whose value is loaded in the background by the future returned from
+    /// `fetch`, which is restarted whenever the key computed by `source` changes. See
+    /// [`create_async_resource`].
+    #[track_caller]
+    pub fn create_async_resource<K, T, E, S, F, Fut>(
+        &mut self,
+        source: S,
+        fetch: F,
+    ) -> Signal<AsyncState<T, E>>
+    where
+        K: PartialEq + Clone + Send + Sync + 'static,
+        T: PartialEq + Send + Sync + 'static,
+        E: PartialEq + Send + Sync + 'static,
+        S: Send + Sync + 'static + Fn(&Rcx) -> K,
+        F: Send + Sync + 'static + Fn(K) -> Fut,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+    {
+        create_async_resource(self.world, self.parent, source, fetch)
+    }
+
+    /// Create a signal which contains the load state of the asset referred to by `handle`.
+    #[track_caller]
+    pub fn create_asset_state_signal<A: Asset>(&mut self, handle: Handle<A>) -> Signal<LoadState> {
+        create_asset_state_signal(self.world, self.parent, handle)
+    }
+
+    /// Create a new [`MutableVec`] in this context.
+    #[track_caller]
+    pub fn create_mutable_vec<T: Send + Sync + 'static>(&mut self, init: Vec<T>) -> MutableVec<T> {
+        create_mutable_vec(self.world, self.parent, init)
+    }
+
+    /// Create a new [`MutableMap`] in this context.
+    #[track_caller]
+    pub fn create_mutable_map<K, V>(&mut self, init: HashMap<K, V>) -> MutableMap<K, V>
+    where
+        K: Send + Sync + 'static,
+        V: Send + Sync + 'static,
+    {
+        create_mutable_map(self.world, self.parent, init)
+    }
+
+    /// Create a new reactive [`Store`] in this context, in which each field of `value` is
+    /// tracked separately.
+    #[track_caller]
+    pub fn create_store<S: Store>(&mut self, value: S) -> S::Handle {
+        create_store(self.world, self.parent, value)
+    }
+
+    /// Create a signal which follows `source`, but only changes once `source` has stopped
+    /// changing for `secs` seconds.
+    #[track_caller]
+    pub fn create_debounced<T: PartialEq + Clone + Send + Sync + 'static>(
+        &mut self,
+        source: Signal<T>,
+        secs: f32,
+    ) -> Signal<T> {
+        create_debounced(self.world, self.parent, source, secs)
+    }
+
+    /// Create a signal which follows `source`, but changes at most once every `secs` seconds.
+    #[track_caller]
+    pub fn create_throttled<T: PartialEq + Clone + Send + Sync + 'static>(
+        &mut self,
+        source: Signal<T>,
+        secs: f32,
+    ) -> Signal<T> {
+        create_throttled(self.world, self.parent, source, secs)
+    }
+
+    /// Create a signal which counts the number of whole periods of `secs` seconds which have
+    /// elapsed since it was created.
+    #[track_caller]
+    pub fn create_interval(&mut self, secs: f32) -> Signal<u64> {
+        create_interval(self.world, self.parent, secs)
+    }
+
+    /// Create a signal which becomes true once `secs` seconds have elapsed.
+    #[track_caller]
+    pub fn create_timeout(&mut self, secs: f32) -> Signal<bool> {
+        create_timeout(self.world, self.parent, secs)
+    }
+
+    /// Create a signal whose value is computed by folding each new event of type `E` into the
+    /// previous value using `reducer`.
+    #[track_caller]
+    pub fn create_event_signal<
+        E: Event + Clone,
+        T: PartialEq + Send + Sync + 'static,
+        F: Send + Sync + 'static + Fn(&mut T, &E),
+    >(
+        &mut self,
+        init: T,
+        reducer: F,
+    ) -> Signal<T> {
+        create_event_signal(self.world, self.parent, init, reducer)
+    }
+
+    /// Create a new [`ReactiveQuery`] in this context. Reading the query from a reactive
+    /// context will cause the reader to react when the set of matching entities changes, or
+    /// when any of the components read by the query change.
+    pub fn create_query<D: ReadOnlyQueryData + 'static, F: QueryFilter + 'static>(
+        &mut self,
+    ) -> ReactiveQuery<D, F> {
+        create_query(self.world, self.parent)
+    }
+
     /// Create a new [`Derived`] in this context. This represents a readable signal which
     /// is computed from other signals. The result is not memoized, but is recomputed whenever
     /// the dependencies change.
@@ -99,12 +252,33 @@ impl<'w> UiBuilder<'w> {
     /// Arguments:
     /// * `compute` - The function that computes the output. This will be called with a single
     ///    parameter, which is an [`Rcx`] object.
+    #[track_caller]
     pub fn create_derived<R: 'static, F: Send + Sync + 'static + Fn(&mut Rcx) -> R>(
         &mut self,
         compute: F,
     ) -> Signal<R> {
         let derived = create_derived(self.world, compute);
-        self.world.entity_mut(self.parent).add_child(derived.id());
+        self.world.entity_mut(derived.id()).set_owner(self.parent);
+        Signal::Derived(derived)
+    }
+
+    /// Create a new cached [`Derived`] in this context. Like [`create_derived`], but the result
+    /// is retained, and the compute function is only called again when one of its dependencies
+    /// has changed. This is useful when an expensive computation is read by many reactions.
+    ///
+    /// Arguments:
+    /// * `compute` - The function that computes the output. This will be called with a single
+    ///    parameter, which is an [`Rcx`] object.
+    #[track_caller]
+    pub fn create_cached_derived<
+        R: Send + Sync + 'static,
+        F: Send + Sync + 'static + Fn(&mut Rcx) -> R,
+    >(
+        &mut self,
+        compute: F,
+    ) -> Signal<R> {
+        let derived = create_cached_derived(self.world, compute);
+        self.world.entity_mut(derived.id()).set_owner(self.parent);
         Signal::Derived(derived)
     }
 
@@ -115,6 +289,7 @@ impl<'w> UiBuilder<'w> {
     /// Arguments:
     /// * `compute` - The function that computes the output. This will be called with a single
     ///    parameter, which is a [`Rcx`] object.
+    #[track_caller]
     pub fn create_memo<
         R: 'static + PartialEq + Send + Sync + Clone,
         F: Send + Sync + 'static + Fn(&mut Rcx) -> R,
@@ -145,13 +320,11 @@ impl<'w> UiBuilder<'w> {
         let mut scope = TrackingScope::new(self.world().last_change_tick());
         let mut reaction = EffectReaction { effect };
         let owner = self.parent;
-        let effect_owner = self.world.spawn_empty().set_parent(owner).id();
+        let effect_owner = self.world.spawn_empty().set_owner(owner).id();
         reaction.react(effect_owner, self.world, &mut scope);
-        self.world.entity_mut(effect_owner).insert((
-            scope,
-            ReactionCell::new(reaction),
-            GhostNode::default(),
-        ));
+        self.world
+            .entity_mut(effect_owner)
+            .insert((scope, ReactionCell::new(reaction)));
         self
     }
 
@@ -250,7 +423,7 @@ impl<F: FnMut(&mut Ecx)> Reaction for EffectReaction<F> {
 pub struct MemoReaction<
     R: 'static + PartialEq + Send + Sync + Clone,
     F: Send + Sync + 'static + Fn(&mut Rcx) -> R,
->(F);
+>(pub(crate) F);
 
 impl<
         R: 'static + PartialEq + Send + Sync + Clone,
@@ -262,6 +435,10 @@ impl<
         let value = (self.0)(&mut rcx);
         world.write_mutable(owner, value);
     }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Memo
+    }
 }
 
 /// A reaction that handles the conditional rendering logic.
@@ -280,8 +457,174 @@ impl<D, F: Send + Sync + Fn(&Rcx) -> D, B: Send + Sync + Fn(D, &mut UiBuilder)>
         // Create a reactive context and call the test condition.
         let re = Rcx::new(world, owner, tracking);
         let deps: D = (self.compute)(&re);
-        world.entity_mut(owner).despawn_descendants();
+        world
+            .entity_mut(owner)
+            .despawn_descendants()
+            .despawn_owned();
         let mut builder = UiBuilder::new(world, owner);
         (self.build)(deps, &mut builder);
     }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Structural
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use bevy::asset::AssetPlugin;
+    use bevy::prelude::*;
+    use bevy_reactor_signals::{AddReactiveAsset, AddReactiveEvent, SignalsPlugin};
+
+    use super::*;
+    use crate::EntityEffectBuilder;
+
+    #[derive(Event, Clone)]
+    struct Ping;
+
+    #[derive(Asset, TypePath)]
+    struct Glyphs(usize);
+
+    #[test]
+    fn test_effect_reads_events() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin).add_reactive_event::<Ping>();
+        let received = Arc::new(AtomicUsize::new(0));
+        let received_inner = received.clone();
+        let targeted = Arc::new(AtomicUsize::new(0));
+        let targeted_inner = targeted.clone();
+        let world = app.world_mut();
+        let root = world.spawn_empty().id();
+        let mut builder = UiBuilder::new(world, root);
+        builder.create_effect(move |ecx| {
+            let pings = ecx.read_events::<Ping>().len();
+            received_inner.fetch_add(pings, Ordering::Relaxed);
+        });
+        // The reading context of a targeted effect is owned by the target entity.
+        builder.spawn_empty().effect(
+            |rcx| rcx.read_events::<Ping>().len(),
+            move |pings, _| {
+                targeted_inner.fetch_add(pings, Ordering::Relaxed);
+            },
+        );
+        app.update();
+        assert_eq!(received.load(Ordering::Relaxed), 0);
+        assert_eq!(targeted.load(Ordering::Relaxed), 0);
+
+        app.world_mut().send_event(Ping);
+        app.world_mut().send_event(Ping);
+        app.update();
+        assert_eq!(received.load(Ordering::Relaxed), 2);
+        assert_eq!(targeted.load(Ordering::Relaxed), 2);
+        app.update();
+        app.world_mut().send_event(Ping);
+        app.update();
+        assert_eq!(received.load(Ordering::Relaxed), 3);
+        assert_eq!(targeted.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_effects_on_one_target_read_events() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin).add_reactive_event::<Ping>();
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let world = app.world_mut();
+        let root = world.spawn_empty().id();
+        let mut builder = UiBuilder::new(world, root);
+        // Each reader has its own position in the queue, even when the readers share an
+        // owner, or read the events through the same derived.
+        let pings = builder.create_derived(|rcx| rcx.read_events::<Ping>().len());
+        let mut target = builder.spawn_empty();
+        for name in ["first", "second"] {
+            let received = received.clone();
+            target.effect(
+                |rcx| rcx.read_events::<Ping>().len(),
+                move |pings, _| {
+                    received.lock().unwrap().push((name, pings));
+                },
+            );
+        }
+        for name in ["derived a", "derived b"] {
+            let received = received.clone();
+            target.effect(
+                move |rcx| pings.get(rcx),
+                move |pings, _| {
+                    received.lock().unwrap().push((name, pings));
+                },
+            );
+        }
+        app.update();
+        received.lock().unwrap().clear();
+
+        app.world_mut().send_event(Ping);
+        app.update();
+        let mut received = received.lock().unwrap().clone();
+        received.sort();
+        assert_eq!(
+            received,
+            vec![
+                ("derived a", 1),
+                ("derived b", 1),
+                ("first", 1),
+                ("second", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_effect_reads_assets() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .add_plugins(SignalsPlugin)
+            .init_asset::<Glyphs>()
+            .add_reactive_asset::<Glyphs>();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen_inner = seen.clone();
+        let world = app.world_mut();
+        let handle = world.resource::<Assets<Glyphs>>().reserve_handle();
+        let reader = handle.clone();
+        let root = world.spawn_empty().id();
+        let mut builder = UiBuilder::new(world, root);
+        builder.create_effect(move |ecx| {
+            let count = ecx.read_asset(&reader).map(|glyphs| glyphs.0);
+            seen_inner.lock().unwrap().push(count);
+        });
+        // Assets read by a derived are tracked by the scope which reads the derived.
+        let reader = handle.clone();
+        let count = builder.create_derived(move |rcx| rcx.read_asset(&reader).map(|g| g.0));
+        let target = builder
+            .spawn_empty()
+            .effect(
+                move |rcx| count.get(rcx),
+                |count, entity| {
+                    entity.insert(Name::new(format!("{:?}", count)));
+                },
+            )
+            .id();
+        app.update();
+        assert_eq!(*seen.lock().unwrap(), vec![None]);
+        assert_eq!(app.world().get::<Name>(target).unwrap().as_str(), "None");
+
+        app.world_mut()
+            .resource_mut::<Assets<Glyphs>>()
+            .insert(&handle, Glyphs(3));
+        app.update();
+        app.update();
+        assert_eq!(*seen.lock().unwrap(), vec![None, Some(3)]);
+        assert_eq!(app.world().get::<Name>(target).unwrap().as_str(), "Some(3)");
+
+        app.world_mut()
+            .resource_mut::<Assets<Glyphs>>()
+            .get_mut(&handle)
+            .unwrap()
+            .0 = 4;
+        app.update();
+        app.update();
+        assert_eq!(*seen.lock().unwrap(), vec![None, Some(3), Some(4)]);
+    }
 }
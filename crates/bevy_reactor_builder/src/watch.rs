@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+use bevy_reactor_signals::{Rcx, Reaction, ReactionCell, SetOwner, Signal, TrackingScope};
+
+use crate::UiBuilder;
+
+/// Trait that abstracts over the value being watched. We use this trait to allow signals to be
+/// passed directly, as well as reactive functions.
+pub trait WatchSource<T>: Send + Sync {
+    fn read(&self, rcx: &Rcx) -> T;
+}
+
+impl<T, F: Send + Sync + Fn(&Rcx) -> T> WatchSource<T> for F {
+    fn read(&self, rcx: &Rcx) -> T {
+        self(rcx)
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> WatchSource<T> for Signal<T> {
+    fn read(&self, rcx: &Rcx) -> T {
+        self.get_clone(rcx)
+    }
+}
+
+pub trait WatchBuilder {
+    /// Watch a reactive value, calling `callback` with the old and new values whenever the
+    /// value changes. The callback is not called for the initial value.
+    ///
+    /// Arguments:
+    /// * `source` - A signal, or a reactive function which computes the value to watch.
+    /// * `callback` - Called with the previous value, the new value, and the world.
+    fn watch<
+        T: PartialEq + Send + Sync + 'static,
+        S: WatchSource<T> + 'static,
+        F: Send + Sync + 'static + FnMut(Option<&T>, &T, &mut World),
+    >(
+        &mut self,
+        source: S,
+        callback: F,
+    ) -> &mut Self;
+
+    /// Like [`watch`](WatchBuilder::watch), but also calls `callback` immediately with the
+    /// initial value. For the initial call, the previous value is `None`.
+    fn watch_immediate<
+        T: PartialEq + Send + Sync + 'static,
+        S: WatchSource<T> + 'static,
+        F: Send + Sync + 'static + FnMut(Option<&T>, &T, &mut World),
+    >(
+        &mut self,
+        source: S,
+        callback: F,
+    ) -> &mut Self;
+
+    /// Watch a reactive value using a custom comparison, for types which don't implement
+    /// `PartialEq`, or for which only some changes are of interest.
+    ///
+    /// Arguments:
+    /// * `source` - A signal, or a reactive function which computes the value to watch.
+    /// * `eq` - Returns true if the old and new values should be considered equal.
+    /// * `immediate` - Whether to call `callback` immediately with the initial value.
+    /// * `callback` - Called with the previous value, the new value, and the world.
+    fn watch_with<
+        T: Send + Sync + 'static,
+        S: WatchSource<T> + 'static,
+        E: Send + Sync + 'static + Fn(&T, &T) -> bool,
+        F: Send + Sync + 'static + FnMut(Option<&T>, &T, &mut World),
+    >(
+        &mut self,
+        source: S,
+        eq: E,
+        immediate: bool,
+        callback: F,
+    ) -> &mut Self;
+}
+
+impl<'w> WatchBuilder for UiBuilder<'w> {
+    fn watch<
+        T: PartialEq + Send + Sync + 'static,
+        S: WatchSource<T> + 'static,
+        F: Send + Sync + 'static + FnMut(Option<&T>, &T, &mut World),
+    >(
+        &mut self,
+        source: S,
+        callback: F,
+    ) -> &mut Self {
+        self.watch_with(source, T::eq, false, callback)
+    }
+
+    fn watch_immediate<
+        T: PartialEq + Send + Sync + 'static,
+        S: WatchSource<T> + 'static,
+        F: Send + Sync + 'static + FnMut(Option<&T>, &T, &mut World),
+    >(
+        &mut self,
+        source: S,
+        callback: F,
+    ) -> &mut Self {
+        self.watch_with(source, T::eq, true, callback)
+    }
+
+    fn watch_with<
+        T: Send + Sync + 'static,
+        S: WatchSource<T> + 'static,
+        E: Send + Sync + 'static + Fn(&T, &T) -> bool,
+        F: Send + Sync + 'static + FnMut(Option<&T>, &T, &mut World),
+    >(
+        &mut self,
+        source: S,
+        eq: E,
+        immediate: bool,
+        callback: F,
+    ) -> &mut Self {
+        let parent = self.parent();
+        let mut watch_owner = self.world_mut().spawn(Name::new("Watch"));
+        watch_owner.set_owner(parent);
+        let watch_owner_id = watch_owner.id();
+
+        // Create a tracking scope and reaction.
+        let mut tracking = TrackingScope::new(watch_owner.world().last_change_tick());
+        let mut reaction = WatchReaction {
+            source,
+            eq,
+            callback,
+            immediate,
+            value: None,
+        };
+
+        // Safety: this should be safe because we don't use watch_owner any more after this
+        // point.
+        let world = unsafe { watch_owner.world_mut() };
+        // Read the initial value.
+        reaction.react(watch_owner_id, world, &mut tracking);
+        world
+            .entity_mut(watch_owner_id)
+            .insert((tracking, ReactionCell::new(reaction)));
+        self
+    }
+}
+
+/// A reaction which calls a callback when the watched value changes.
+struct WatchReaction<T, S: WatchSource<T>, E: Fn(&T, &T) -> bool, F>
+where
+    Self: Send + Sync,
+{
+    source: S,
+    eq: E,
+    callback: F,
+    immediate: bool,
+
+    /// The most recent value, or `None` if the source has not been read yet.
+    value: Option<T>,
+}
+
+impl<
+        T: Send + Sync,
+        S: WatchSource<T>,
+        E: Send + Sync + Fn(&T, &T) -> bool,
+        F: Send + Sync + FnMut(Option<&T>, &T, &mut World),
+    > Reaction for WatchReaction<T, S, E, F>
+{
+    fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+        let value = self.source.read(&Rcx::new(world, owner, tracking));
+        match self.value.take() {
+            None if self.immediate => (self.callback)(None, &value, world),
+            None => {}
+            Some(old) if (self.eq)(&old, &value) => {}
+            Some(old) => (self.callback)(Some(&old), &value, world),
+        }
+        self.value = Some(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use bevy_reactor_signals::{create_mutable, SignalsPlugin};
+
+    use super::*;
+
+    #[test]
+    fn test_watch() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin);
+        let world = app.world_mut();
+        let owner = world.spawn_empty().id();
+        let selection = create_mutable(world, owner, 1);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let log_changes = log.clone();
+        let log_immediate = log.clone();
+        let mut builder = UiBuilder::new(world, owner);
+        builder
+            .watch(selection.signal(), move |old, new, _| {
+                log_changes.lock().unwrap().push((old.copied(), *new));
+            })
+            .watch_immediate(
+                move |rcx: &Rcx| selection.get(rcx) / 2,
+                move |old, new, _| {
+                    log_immediate
+                        .lock()
+                        .unwrap()
+                        .push((old.copied(), *new + 100));
+                },
+            );
+        assert_eq!(*log.lock().unwrap(), vec![(None, 100)]);
+
+        selection.set(app.world_mut(), 2);
+        app.update();
+        selection.set(app.world_mut(), 3);
+        app.update();
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![(None, 100), (Some(1), 2), (Some(0), 101), (Some(2), 3)]
+        );
+    }
+}
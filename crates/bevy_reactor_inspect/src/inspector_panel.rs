@@ -3,8 +3,8 @@ use bevy::{
     ecs::{observer::ObserverState, system::SystemIdMarker, world::DeferredWorld},
     pbr::{DirectionalLight, PointLight},
     prelude::{
-        Camera2d, Camera3d, Children, Click, Component, Entity, In, Mesh3d, Parent, Pointer, Query,
-        ResMut, Resource, Trigger, Without, World,
+        Camera2d, Camera3d, Children, Click, Component, Entity, In, Mesh3d, Parent, Pointer,
+        Trigger, Without, World,
     },
     ui::{self, experimental::GhostNode, Node},
     window::{Monitor, Window},
@@ -22,7 +22,7 @@ use bevy_reactor_obsidian::{
     prelude::{DisclosureToggle, ScrollView},
     typography,
 };
-use bevy_reactor_signals::ReactionCell;
+use bevy_reactor_signals::{OwnedBy, ReactionCell};
 
 fn style_panel(sb: &mut StyleBuilder) {
     sb.position(ui::PositionType::Absolute)
@@ -78,12 +78,18 @@ impl UiTemplate for TopLevelItemList {
                 .content_style((typography::text_default, style_item_list_content))
                 .scroll_enable_y(true)
                 .children(|builder| {
+                    let top_level_entities = builder.create_query::<Entity, (
+                        Without<Parent>,
+                        Without<OwnedBy>,
+                        Without<InspectorPanelRoot>,
+                        Without<ObserverState>,
+                        Without<SystemIdMarker>,
+                    )>();
                     builder.for_each(
-                        |rcx| {
-                            rcx.read_resource::<TopLevelEntities>()
-                                .0
-                                .clone()
-                                .into_iter()
+                        move |rcx| {
+                            let mut entities = rcx.read_query(&top_level_entities);
+                            entities.sort_unstable();
+                            entities.into_iter()
                         },
                         |ent, builder| {
                             builder.invoke(EntityTreeNode(*ent));
@@ -95,30 +101,6 @@ impl UiTemplate for TopLevelItemList {
     }
 }
 
-#[derive(Resource, Default)]
-pub(crate) struct TopLevelEntities(Vec<Entity>);
-
-#[allow(clippy::type_complexity)]
-pub fn copy_top_level_entities(
-    q_entities: Query<
-        Entity,
-        (
-            Without<Parent>,
-            Without<InspectorPanelRoot>,
-            Without<ObserverState>,
-            Without<SystemIdMarker>,
-        ),
-    >,
-    mut r_list: ResMut<TopLevelEntities>,
-) {
-    let mut entities: Vec<Entity> = q_entities.iter().collect();
-    // TODO: Sort
-    if entities != r_list.0 {
-        println!("Entity count: {}", entities.len());
-        std::mem::swap(&mut entities, &mut r_list.0);
-    }
-}
-
 fn style_tree_node(sb: &mut StyleBuilder) {
     sb.display(ui::Display::Flex)
         .flex_direction(ui::FlexDirection::Column);
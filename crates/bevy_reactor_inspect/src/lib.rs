@@ -1,8 +1,8 @@
-use bevy::app::{Plugin, Startup, Update};
+use bevy::app::{Plugin, Startup};
 use bevy_mod_stylebuilder::StyleBuilderPlugin;
 use bevy_reactor_obsidian::ObsidianUiPlugin;
 use bevy_reactor_signals::SignalsPlugin;
-use inspector_panel::{copy_top_level_entities, create_inspector_panel, TopLevelEntities};
+use inspector_panel::create_inspector_panel;
 
 mod inspector_panel;
 
@@ -10,9 +10,7 @@ pub struct WorldInspector;
 
 impl Plugin for WorldInspector {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.init_resource::<TopLevelEntities>()
-            .add_plugins((SignalsPlugin, StyleBuilderPlugin, ObsidianUiPlugin))
-            .add_systems(Startup, create_inspector_panel)
-            .add_systems(Update, copy_top_level_entities);
+        app.add_plugins((SignalsPlugin, StyleBuilderPlugin, ObsidianUiPlugin))
+            .add_systems(Startup, create_inspector_panel);
     }
 }
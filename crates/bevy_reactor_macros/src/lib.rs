@@ -0,0 +1,271 @@
+//! Derive macros for `bevy_reactor_signals`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, GenericArgument, PathArguments, Type};
+
+/// How a field of a store is tracked.
+enum FieldKind {
+    /// The field is held in its own `Mutable`.
+    Value,
+    /// The field is itself a store.
+    Nested,
+    /// The field is a `Vec` whose elements are stores.
+    Vec(Box<Type>),
+}
+
+/// Derive the `Store` trait, which allows a struct to be held in a reactive store where each
+/// field is tracked separately.
+///
+/// By default each field is held in its own `Mutable`, and must implement `Clone` and
+/// `PartialEq`. Fields can be annotated with `#[store(nested)]` if the field type also
+/// derives `Store`, or `#[store(vec)]` if the field is a `Vec` of a type which derives `Store`.
+#[proc_macro_derive(Store, attributes(store))]
+pub fn derive_store(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_store(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_store(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "Store cannot be derived for generic types",
+        ));
+    }
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "Store can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            "Store can only be derived for structs with named fields",
+        ));
+    };
+
+    let crate_path = quote!(::bevy_reactor_signals);
+    let private = quote!(#crate_path::__private);
+    let name = &input.ident;
+    let vis = &input.vis;
+    let handle = format_ident!("{}Store", name);
+
+    let mut handle_fields = Vec::new();
+    let mut inits = Vec::new();
+    let mut accessors = Vec::new();
+    for field in fields.named.iter() {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let mutable_fn = format_ident!("{}_mutable", ident);
+        match field_kind(field)? {
+            FieldKind::Value => {
+                let set_fn = format_ident!("set_{}", ident);
+                handle_fields.push(quote!(#ident: #crate_path::Mutable<#ty>));
+                inits.push(quote! {
+                    #ident: #crate_path::create_mutable(world, __entity, self.#ident)
+                });
+                let read_doc = format!("Read the `{}` field, tracking only that field.", ident);
+                let set_doc = format!("Set the `{}` field.", ident);
+                let mutable_doc = format!("The `Mutable` holding the `{}` field.", ident);
+                accessors.push(quote! {
+                    #[doc = #read_doc]
+                    pub fn #ident<R: #crate_path::ReadMutable>(&self, cx: &R) -> #ty {
+                        cx.read_mutable_clone(&self.#ident)
+                    }
+
+                    #[doc = #set_doc]
+                    pub fn #set_fn<W: #crate_path::WriteMutable>(&self, cx: &mut W, value: #ty) {
+                        cx.write_mutable(self.#ident.id(), value);
+                    }
+
+                    #[doc = #mutable_doc]
+                    pub fn #mutable_fn(&self) -> #crate_path::Mutable<#ty> {
+                        self.#ident
+                    }
+                });
+            }
+            FieldKind::Nested => {
+                handle_fields.push(quote!(#ident: <#ty as #crate_path::Store>::Handle));
+                inits.push(quote! {
+                    #ident: #crate_path::Store::create_store(self.#ident, world, __entity)
+                });
+                let doc = format!("The store for the `{}` field.", ident);
+                accessors.push(quote! {
+                    #[doc = #doc]
+                    pub fn #ident(&self) -> <#ty as #crate_path::Store>::Handle {
+                        self.#ident
+                    }
+                });
+            }
+            FieldKind::Vec(elt) => {
+                let elt_handle = quote!(<#elt as #crate_path::Store>::Handle);
+                let push_fn = format_ident!("push_{}", ident);
+                let remove_fn = format_ident!("remove_{}", ident);
+                handle_fields.push(quote!(#ident: #crate_path::Mutable<Vec<#elt_handle>>));
+                inits.push(quote! {
+                    #ident: {
+                        let mut items: Vec<#elt_handle> = Vec::new();
+                        for item in self.#ident {
+                            items.push(#crate_path::Store::create_store(item, world, __entity));
+                        }
+                        #crate_path::create_mutable(world, __entity, items)
+                    }
+                });
+                let read_doc = format!(
+                    "Read the stores for the elements of the `{}` field. This only tracks which \
+                     elements are present; each element's fields are tracked separately.",
+                    ident
+                );
+                let push_doc = format!("Append an element to the `{}` field.", ident);
+                let remove_doc = format!(
+                    "Remove the element at `index` from the `{}` field, despawning its store.",
+                    ident
+                );
+                let mutable_doc = format!(
+                    "The `Mutable` holding the element stores of the `{}` field.",
+                    ident
+                );
+                accessors.push(quote! {
+                    #[doc = #read_doc]
+                    pub fn #ident<R: #crate_path::ReadMutable>(&self, cx: &R) -> Vec<#elt_handle> {
+                        cx.read_mutable_clone(&self.#ident)
+                    }
+
+                    #[doc = #push_doc]
+                    #[track_caller]
+                    pub fn #push_fn(&self, world: &mut #private::World, value: #elt) -> #elt_handle {
+                        let item = #crate_path::Store::create_store(value, world, self.__entity);
+                        #crate_path::WriteMutable::update_mutable(
+                            world,
+                            self.#ident.id(),
+                            |mut items: #private::Mut<Vec<#elt_handle>>| items.push(item),
+                        );
+                        item
+                    }
+
+                    #[doc = #remove_doc]
+                    ///
+                    /// # Panics
+                    ///
+                    /// Panics if `index` is out of bounds.
+                    pub fn #remove_fn(&self, world: &mut #private::World, index: usize) {
+                        let mut removed = None;
+                        #crate_path::WriteMutable::update_mutable(
+                            world,
+                            self.#ident.id(),
+                            |mut items: #private::Mut<Vec<#elt_handle>>| {
+                                removed = Some(items.remove(index));
+                            },
+                        );
+                        if let Some(item) = removed {
+                            #private::despawn_store(world, &item);
+                        }
+                    }
+
+                    #[doc = #mutable_doc]
+                    pub fn #mutable_fn(&self) -> #crate_path::Mutable<Vec<#elt_handle>> {
+                        self.#ident
+                    }
+                });
+            }
+        }
+    }
+
+    let handle_doc = format!(
+        "Reactive store for [`{}`], in which each field is tracked separately.",
+        name
+    );
+    Ok(quote! {
+        #[doc = #handle_doc]
+        #[derive(Clone, Copy)]
+        #vis struct #handle {
+            __entity: #private::Entity,
+            #(#handle_fields,)*
+        }
+
+        impl #handle {
+            #(#accessors)*
+        }
+
+        impl PartialEq for #handle {
+            fn eq(&self, other: &Self) -> bool {
+                self.__entity == other.__entity
+            }
+        }
+
+        impl ::std::fmt::Debug for #handle {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_tuple(stringify!(#handle)).field(&self.__entity).finish()
+            }
+        }
+
+        impl #crate_path::StoreHandle for #handle {
+            fn id(&self) -> #private::Entity {
+                self.__entity
+            }
+        }
+
+        impl #crate_path::Store for #name {
+            type Handle = #handle;
+
+            #[track_caller]
+            fn create_store(
+                self,
+                world: &mut #private::World,
+                parent: #private::Entity,
+            ) -> #handle {
+                let __entity = #private::spawn_store(world, parent);
+                #handle {
+                    __entity,
+                    #(#inits,)*
+                }
+            }
+        }
+    })
+}
+
+/// Determine how a field should be tracked from its `#[store(...)]` attribute.
+fn field_kind(field: &syn::Field) -> syn::Result<FieldKind> {
+    let mut kind = FieldKind::Value;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("store")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("nested") {
+                kind = FieldKind::Nested;
+                Ok(())
+            } else if meta.path.is_ident("vec") {
+                let elt = vec_element(&field.ty).ok_or_else(|| {
+                    syn::Error::new_spanned(&field.ty, "#[store(vec)] requires a Vec field")
+                })?;
+                kind = FieldKind::Vec(Box::new(elt));
+                Ok(())
+            } else {
+                Err(meta.error("expected `nested` or `vec`"))
+            }
+        })?;
+    }
+    Ok(kind)
+}
+
+/// If `ty` is `Vec<T>`, return `T`.
+fn vec_element(ty: &Type) -> Option<Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Vec" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(elt) => Some(elt.clone()),
+        _ => None,
+    }
+}
@@ -1,6 +1,6 @@
-use bevy::{prelude::*, ui::experimental::GhostNode};
+use bevy::prelude::*;
 use bevy_reactor_builder::UiBuilder;
-use bevy_reactor_signals::Signal;
+use bevy_reactor_signals::{SetOwner, Signal};
 
 /// Plugin that runs the timers for bistable transitions.
 pub struct BistableTransitionPlugin;
@@ -79,7 +79,8 @@ impl<'w> CreateBistableTransition for UiBuilder<'w> {
         delay: f32,
     ) -> Signal<BistableTransitionState> {
         // Create an entity to hold the state machine.
-        let entity = self.spawn(GhostNode::default()).id();
+        let parent = self.parent();
+        let entity = self.world_mut().spawn_empty().set_owner(parent).id();
 
         // Effect which updates the state machine when the `open` signal changes.
         self.create_effect(move |ve| {
@@ -6,7 +6,7 @@ use bevy_reactor_builder::{
     CondBuilder, CreateChilden, EntityStyleBuilder, InvokeUiTemplate, TextBuilder, UiBuilder,
     UiTemplate,
 };
-use bevy_reactor_signals::{Callback, IntoSignal, RunCallback, Signal};
+use bevy_reactor_signals::{Callback, IntoSignal, Rcx, RunCallback, Signal};
 
 use crate::{colors, cursor::StyleBuilderCursor, prelude::RoundedCorners, typography};
 
@@ -180,11 +180,17 @@ impl UiTemplate for SpinBox {
     fn build(&self, builder: &mut UiBuilder) {
         let spinbox_id = builder.spawn((Node::default(), Name::new("Spinbox"))).id();
         let drag_state = builder.create_mutable::<DragState>(DragState::default());
+        // Show buttons when spinbox is wide enough.
         let show_buttons = builder.create_derived(move |rcx| {
-            // Show buttons when spinbox is wide enough.
             let node = rcx.read_component::<ComputedNode>(spinbox_id).unwrap();
             node.size().x >= 48.
         });
+        // The width depends on the result of layout, so the conditions which read it react
+        // after layout rather than one frame later.
+        let show_buttons_after_layout = move |rcx: &Rcx| {
+            rcx.set_schedule(PostUpdate);
+            show_buttons.get(rcx)
+        };
 
         // Pain point: Need to capture all props for closures.
         let min = self.min;
@@ -218,7 +224,7 @@ impl UiTemplate for SpinBox {
             .styles((style_spinbox, self.style.clone()))
             .create_children(|builder| {
                 builder.cond(
-                    show_buttons,
+                    show_buttons_after_layout,
                     move |builder| {
                         builder.invoke(
                             IconButton::new(
@@ -306,7 +312,7 @@ impl UiTemplate for SpinBox {
                         });
                     });
                 builder.cond(
-                    show_buttons,
+                    show_buttons_after_layout,
                     move |builder| {
                         builder.invoke(
                             IconButton::new(
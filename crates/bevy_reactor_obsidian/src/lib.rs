@@ -1,4 +1,5 @@
-use bevy::{asset::embedded_asset, prelude::*};
+use bevy::{asset::embedded_asset, prelude::*, ui::UiSystem};
+use bevy_reactor_signals::{AddReactionSchedule, ReactionSet};
 
 pub mod animation;
 pub mod colors;
@@ -94,7 +95,10 @@ impl Plugin for ObsidianUiPlugin {
                 hover_signal::update_hover_states,
                 cursor::update_cursor,
             ),
-        );
+        )
+        // Some widgets, such as `SpinBox`, react to the computed layout.
+        .add_reaction_schedule(PostUpdate)
+        .configure_sets(PostUpdate, ReactionSet.after(UiSystem::Layout));
         // .init_resource::<RecentColors>()
         // .add_systems(PostUpdate, floating::position_floating);
     }
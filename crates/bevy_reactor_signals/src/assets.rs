@@ -0,0 +1,247 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    asset::{Asset, AssetEvent, AssetId, AssetServer, Assets, Handle, LoadState},
+    core::Name,
+    ecs::{component::Tick, event::EventCursor},
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::{
+    create_mutable, tracking_scope::PolledDependency, Reaction, ReactionCell, ReactionKind,
+    ReactionSet, Signal, TrackingScope, WriteMutable,
+};
+
+/// Resource which records changes to the assets of type `A` which have been read by reactive
+/// contexts.
+#[derive(Resource)]
+pub(crate) struct AssetSubscribers<A: Asset> {
+    /// Cursor used to read new asset events.
+    cursor: EventCursor<AssetEvent<A>>,
+
+    /// The change history of watched assets. This is behind a mutex because assets are read
+    /// from reactive contexts which only have an immutable world.
+    changes: Mutex<AssetChanges<A>>,
+}
+
+/// The change history of watched assets.
+struct AssetChanges<A: Asset> {
+    /// Counter which is incremented for each change to a watched asset.
+    generation: u64,
+
+    /// The generation of the most recent change to each watched asset.
+    watched: HashMap<AssetId<A>, u64>,
+}
+
+impl<A: Asset> Default for AssetSubscribers<A> {
+    fn default() -> Self {
+        Self {
+            cursor: EventCursor::default(),
+            changes: Mutex::new(AssetChanges {
+                generation: 0,
+                watched: HashMap::default(),
+            }),
+        }
+    }
+}
+
+/// Add a dependency on the asset `id` to `scope`, so that the scope reacts when the asset is
+/// loaded, modified or removed.
+pub(crate) fn track_asset<A: Asset>(world: &World, id: AssetId<A>, scope: &mut TrackingScope) {
+    let Some(subscribers) = world.get_resource::<AssetSubscribers<A>>() else {
+        panic!(
+            "Asset {} must be registered with add_reactive_asset before it can be read",
+            std::any::type_name::<A>()
+        );
+    };
+    let mut changes = subscribers.changes.lock().unwrap();
+    let seen = changes.generation;
+    changes.watched.entry(id).or_insert(seen);
+    scope.track_polled(Arc::new(AssetDependency { id, seen }));
+}
+
+/// Dependency on changes to a single asset.
+struct AssetDependency<A: Asset> {
+    id: AssetId<A>,
+
+    /// The generation at the time the asset was read.
+    seen: u64,
+}
+
+impl<A: Asset> PolledDependency for AssetDependency<A> {
+    fn changed(&self, world: &World, _last_run: Tick, _this_run: Tick) -> bool {
+        let Some(subscribers) = world.get_resource::<AssetSubscribers<A>>() else {
+            return false;
+        };
+        let changes = subscribers.changes.lock().unwrap();
+        // The asset is no longer watched once it has been removed, which is itself a change.
+        match changes.watched.get(&self.id) {
+            Some(generation) => *generation > self.seen,
+            None => true,
+        }
+    }
+}
+
+/// System which records when watched assets are loaded, modified or removed. Removed and
+/// unused assets stop being watched until they are read again.
+fn record_asset_changes<A: Asset>(world: &mut World) {
+    world.resource_scope(|world, mut subscribers: Mut<AssetSubscribers<A>>| {
+        let events = world.resource::<Events<AssetEvent<A>>>();
+        let subscribers = subscribers.bypass_change_detection();
+        let changes = subscribers.changes.get_mut().unwrap();
+        for event in subscribers.cursor.read(events) {
+            match event {
+                AssetEvent::Added { id }
+                | AssetEvent::Modified { id }
+                | AssetEvent::LoadedWithDependencies { id } => {
+                    if let Some(generation) = changes.watched.get_mut(id) {
+                        changes.generation += 1;
+                        *generation = changes.generation;
+                    }
+                }
+                AssetEvent::Removed { id } | AssetEvent::Unused { id } => {
+                    changes.watched.remove(id);
+                }
+            }
+        }
+    });
+}
+
+/// Extension trait which allows assets to be read from reactive contexts.
+pub trait AddReactiveAsset {
+    /// Register an asset type which can be read by reactions, using
+    /// [`Rcx::read_asset`](crate::Rcx::read_asset) or [`create_asset_state_signal`]. The asset
+    /// type must already have been added to the app with `init_asset`.
+    fn add_reactive_asset<A: Asset>(&mut self) -> &mut Self;
+}
+
+impl AddReactiveAsset for App {
+    fn add_reactive_asset<A: Asset>(&mut self) -> &mut Self {
+        if self.world().contains_resource::<AssetSubscribers<A>>() {
+            return self;
+        }
+        self.init_resource::<AssetSubscribers<A>>()
+            .add_systems(Update, record_asset_changes::<A>.before(ReactionSet))
+    }
+}
+
+/// Return the load state of an asset. Assets which were added directly, rather than loaded
+/// by the [`AssetServer`], are considered loaded while they are present.
+fn asset_load_state<A: Asset>(world: &World, id: AssetId<A>) -> LoadState {
+    if world.resource::<Assets<A>>().contains(id) {
+        return LoadState::Loaded;
+    }
+    match world
+        .get_resource::<AssetServer>()
+        .map(|server| server.load_state(id))
+    {
+        Some(LoadState::Loaded) | None => LoadState::NotLoaded,
+        Some(state) => state,
+    }
+}
+
+/// Reaction which copies the load state of an asset to its output.
+struct AssetStateReaction<A: Asset> {
+    handle: Handle<A>,
+}
+
+impl<A: Asset> Reaction for AssetStateReaction<A> {
+    fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+        let id = self.handle.id();
+        track_asset(world, id, tracking);
+        let state = asset_load_state(world, id);
+        // `LoadState` isn't comparable, so only the kind of state is compared.
+        world.update_mutable(owner, |mut value: Mut<LoadState>| {
+            if std::mem::discriminant(&*value) != std::mem::discriminant(&state) {
+                *value = state;
+            }
+        });
+    }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Memo
+    }
+}
+
+/// Create a signal, owned by `parent`, which contains the load state of the asset referred to
+/// by `handle`. The signal changes when the asset is loaded or removed, or fails to load. The
+/// asset type must have been registered with [`AddReactiveAsset::add_reactive_asset`].
+#[track_caller]
+pub fn create_asset_state_signal<A: Asset>(
+    world: &mut World,
+    parent: Entity,
+    handle: Handle<A>,
+) -> Signal<LoadState> {
+    let mut scope = TrackingScope::new(world.change_tick());
+    let state = asset_load_state(world, handle.id());
+    let mutable = create_mutable(world, parent, state);
+    let mut reaction = AssetStateReaction { handle };
+    reaction.react(mutable.id(), world, &mut scope);
+    world.entity_mut(mutable.id()).insert((
+        ReactionCell::new(reaction),
+        scope,
+        Name::new(format!("AssetState::<{}>", std::any::type_name::<A>())),
+    ));
+    Signal::Mutable(mutable)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+    use crate::{create_derived, Rcx, ReadDerived, SignalsPlugin};
+
+    #[derive(Asset, TypePath)]
+    struct Glyphs(usize);
+
+    #[test]
+    fn test_asset_signals() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .add_plugins(SignalsPlugin)
+            .init_asset::<Glyphs>()
+            .add_reactive_asset::<Glyphs>();
+        let world = app.world_mut();
+        let owner = world.spawn_empty().id();
+        let handle = world.resource::<Assets<Glyphs>>().reserve_handle();
+        let state = create_asset_state_signal(world, owner, handle.clone());
+        let reader = handle.clone();
+        let count = create_derived(world, move |rcx: &mut Rcx| {
+            rcx.read_asset(&reader).map(|g| g.0)
+        });
+        assert!(matches!(state.get_clone(app.world()), LoadState::NotLoaded));
+        assert_eq!(app.world().read_derived(&count), None);
+        app.update();
+
+        app.world_mut()
+            .resource_mut::<Assets<Glyphs>>()
+            .insert(&handle, Glyphs(3));
+        app.update();
+        app.update();
+        assert!(matches!(state.get_clone(app.world()), LoadState::Loaded));
+        assert_eq!(app.world().read_derived(&count), Some(3));
+
+        app.world_mut()
+            .resource_mut::<Assets<Glyphs>>()
+            .remove(&handle);
+        app.update();
+        app.update();
+        assert!(matches!(state.get_clone(app.world()), LoadState::NotLoaded));
+
+        // Assets stop being watched once all of their readers are gone.
+        app.world_mut()
+            .resource_mut::<Assets<Glyphs>>()
+            .insert(&handle, Glyphs(4));
+        app.update();
+        let world = app.world_mut();
+        world.entity_mut(owner).despawn_recursive();
+        world.despawn(count.id());
+        drop(handle);
+        app.update();
+        app.update();
+        let subscribers = app.world().resource::<AssetSubscribers<Glyphs>>();
+        assert!(subscribers.changes.lock().unwrap().watched.is_empty());
+    }
+}
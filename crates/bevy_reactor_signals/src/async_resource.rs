@@ -0,0 +1,252 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    core::Name,
+    ecs::component::Tick,
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+
+use crate::{
+    create_mutable, tracking_scope::PolledDependency, Rcx, Reaction, ReactionCell, ReactionKind,
+    Signal, TrackingScope, WriteMutable,
+};
+
+/// The state of an asynchronous resource.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AsyncState<T, E> {
+    /// The value is being loaded.
+    Pending,
+    /// The value has been loaded.
+    Ready(T),
+    /// Loading the value failed.
+    Error(E),
+}
+
+/// The state of an asynchronous resource, without its value. This is useful as the test
+/// value for a `switch`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AsyncStatus {
+    /// The value is being loaded.
+    Pending,
+    /// The value has been loaded.
+    Ready,
+    /// Loading the value failed.
+    Error,
+}
+
+impl<T, E> AsyncState<T, E> {
+    /// Return the status of the resource.
+    pub fn status(&self) -> AsyncStatus {
+        match self {
+            AsyncState::Pending => AsyncStatus::Pending,
+            AsyncState::Ready(_) => AsyncStatus::Ready,
+            AsyncState::Error(_) => AsyncStatus::Error,
+        }
+    }
+
+    /// Returns true if the value is being loaded.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, AsyncState::Pending)
+    }
+
+    /// Returns true if the value has been loaded.
+    pub fn is_ready(&self) -> bool {
+        matches!(self, AsyncState::Ready(_))
+    }
+
+    /// Returns true if loading the value failed.
+    pub fn is_error(&self) -> bool {
+        matches!(self, AsyncState::Error(_))
+    }
+
+    /// Return the loaded value, if there is one.
+    pub fn ready(&self) -> Option<&T> {
+        match self {
+            AsyncState::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return the error, if loading the value failed.
+    pub fn error(&self) -> Option<&E> {
+        match self {
+            AsyncState::Error(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<T, E> From<Result<T, E>> for AsyncState<T, E> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => AsyncState::Ready(value),
+            Err(err) => AsyncState::Error(err),
+        }
+    }
+}
+
+/// Slot into which a background task writes its result. This is also a dependency which
+/// changes when the result arrives.
+struct TaskResult<T, E>(Mutex<Option<Result<T, E>>>);
+
+impl<T: Send, E: Send> PolledDependency for TaskResult<T, E> {
+    fn changed(&self, _world: &World, _last_run: Tick, _this_run: Tick) -> bool {
+        self.0.lock().unwrap().is_some()
+    }
+}
+
+/// The task which is loading the current value of an async resource.
+struct PendingTask<T, E> {
+    result: Arc<TaskResult<T, E>>,
+
+    /// Dropping the task cancels it.
+    _task: Task<()>,
+}
+
+/// Reaction which starts a task whenever the source key changes, and copies the result of
+/// the task to its output.
+struct AsyncResourceReaction<K, T, E, S, F> {
+    source: S,
+    fetch: F,
+    key: Option<K>,
+    pending: Option<PendingTask<T, E>>,
+}
+
+impl<K, T, E, S, F, Fut> Reaction for AsyncResourceReaction<K, T, E, S, F>
+where
+    K: PartialEq + Clone + Send + Sync + 'static,
+    T: PartialEq + Send + Sync + 'static,
+    E: PartialEq + Send + Sync + 'static,
+    S: Send + Sync + Fn(&Rcx) -> K,
+    F: Send + Sync + Fn(K) -> Fut,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+{
+    fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+        let key = (self.source)(&Rcx::new(world, owner, tracking));
+        if self.key.as_ref() != Some(&key) {
+            // Replacing the pending task cancels any stale one.
+            let result = Arc::new(TaskResult(Mutex::new(None)));
+            let slot = result.clone();
+            let future = (self.fetch)(key.clone());
+            let task = AsyncComputeTaskPool::get().spawn(async move {
+                let value = future.await;
+                *slot.0.lock().unwrap() = Some(value);
+            });
+            self.pending = Some(PendingTask {
+                result,
+                _task: task,
+            });
+            self.key = Some(key);
+            world.write_mutable(owner, AsyncState::<T, E>::Pending);
+        }
+
+        if let Some(pending) = self.pending.as_ref() {
+            let result = pending.result.0.lock().unwrap().take();
+            match result {
+                Some(result) => {
+                    self.pending = None;
+                    world.write_mutable(owner, AsyncState::from(result));
+                }
+                None => tracking.track_polled(pending.result.clone()),
+            }
+        }
+    }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Memo
+    }
+}
+
+/// Create a signal, owned by `parent`, whose value is loaded in the background. `source` is
+/// a reactive function which computes the key of the value to load, and `fetch` returns a
+/// future which loads the value for a key. The future is run on the
+/// [`AsyncComputeTaskPool`]; whenever the key changes, the signal returns to
+/// [`AsyncState::Pending`] and the task which was loading the previous key is cancelled.
+#[track_caller]
+pub fn create_async_resource<K, T, E, S, F, Fut>(
+    world: &mut World,
+    parent: Entity,
+    source: S,
+    fetch: F,
+) -> Signal<AsyncState<T, E>>
+where
+    K: PartialEq + Clone + Send + Sync + 'static,
+    T: PartialEq + Send + Sync + 'static,
+    E: PartialEq + Send + Sync + 'static,
+    S: Send + Sync + 'static + Fn(&Rcx) -> K,
+    F: Send + Sync + 'static + Fn(K) -> Fut,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+{
+    let mut scope = TrackingScope::new(world.change_tick());
+    let mutable = create_mutable(world, parent, AsyncState::<T, E>::Pending);
+    let mut reaction = AsyncResourceReaction {
+        source,
+        fetch,
+        key: None,
+        pending: None,
+    };
+    reaction.react(mutable.id(), world, &mut scope);
+    world.entity_mut(mutable.id()).insert((
+        ReactionCell::new(reaction),
+        scope,
+        Name::new(format!("AsyncResource::<{}>", std::any::type_name::<T>())),
+    ));
+    mutable.signal()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use bevy::tasks::TaskPool;
+
+    use super::*;
+    use crate::{CreateMutable, SignalsPlugin};
+
+    /// Run the app until `done` returns true.
+    fn update_until(app: &mut App, done: impl Fn(&World) -> bool) {
+        let start = Instant::now();
+        while !done(app.world()) {
+            assert!(start.elapsed() < Duration::from_secs(5), "Timed out");
+            app.update();
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn test_async_resource() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin);
+        let world = app.world_mut();
+        let owner = world.spawn_empty().id();
+        let path = world.create_mutable("a.txt");
+        let contents = create_async_resource(
+            world,
+            owner,
+            move |rcx| path.get(rcx),
+            |path: &'static str| async move {
+                match path {
+                    "a.txt" => Ok(path.len()),
+                    _ => Err(format!("{} not found", path)),
+                }
+            },
+        );
+        app.update();
+        update_until(&mut app, |world| !contents.get_clone(world).is_pending());
+        assert_eq!(contents.get_clone(app.world()), AsyncState::Ready(5));
+        assert_eq!(contents.get_clone(app.world()).status(), AsyncStatus::Ready);
+
+        path.set(app.world_mut(), "b.txt");
+        app.update();
+        update_until(&mut app, |world| !contents.get_clone(world).is_pending());
+        assert_eq!(
+            contents.get_clone(app.world()).error().unwrap(),
+            "b.txt not found"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use bevy::{
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+};
+
+use crate::{dependency_index::record_write, Ecx};
+
+/// Records the mutables which have been written during a batch.
+#[derive(Resource, Default)]
+pub(crate) struct MutableBatch {
+    /// The number of batches currently open; batches can be nested.
+    depth: usize,
+
+    /// Mutable cells which have been written during the batch, and which need to be marked
+    /// as changed when the batch ends.
+    writes: Vec<(Entity, ComponentId)>,
+}
+
+/// Returns true if mutable writes should be deferred until the end of a batch.
+pub(crate) fn is_batching(world: &World) -> bool {
+    world
+        .get_resource::<MutableBatch>()
+        .is_some_and(|batch| batch.depth > 0)
+}
+
+/// Record that a mutable cell was written during a batch.
+pub(crate) fn record_batch_write(
+    world: &mut DeferredWorld,
+    entity: Entity,
+    component: ComponentId,
+) {
+    let mut batch = world.resource_mut::<MutableBatch>();
+    if !batch.writes.contains(&(entity, component)) {
+        batch.writes.push((entity, component));
+    }
+}
+
+fn begin_batch(world: &mut DeferredWorld) -> bool {
+    let Some(mut batch) = world.get_resource_mut::<MutableBatch>() else {
+        return false;
+    };
+    batch.depth += 1;
+    true
+}
+
+fn end_batch(world: &mut DeferredWorld) {
+    let mut batch = world.resource_mut::<MutableBatch>();
+    batch.depth -= 1;
+    if batch.depth > 0 {
+        return;
+    }
+    let writes = std::mem::take(&mut batch.writes);
+    for (entity, component) in writes {
+        let Ok(mut entt) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        if let Ok(mut cell) = entt.get_mut_by_id(component) {
+            cell.set_changed();
+            record_write(world, entity, component);
+        }
+    }
+}
+
+/// Trait for contexts which can group a number of mutable writes into a batch. Within a
+/// batch, writes to [`Mutable`](crate::Mutable)s take effect immediately, but change
+/// notification is deferred until the outermost batch ends. This means that reactions which
+/// depend on several of the mutables will run once, and see all of the new values, rather
+/// than observing a partial update.
+pub trait BatchMutations {
+    /// Run a closure as a batch, returning its result.
+    fn batch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R;
+}
+
+impl BatchMutations for World {
+    fn batch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.init_resource::<MutableBatch>();
+        begin_batch(&mut DeferredWorld::from(&mut *self));
+        let result = f(self);
+        end_batch(&mut DeferredWorld::from(&mut *self));
+        result
+    }
+}
+
+impl<'w> BatchMutations for DeferredWorld<'w> {
+    /// Run a closure as a batch. Because a `DeferredWorld` cannot insert resources, this
+    /// requires the [`SignalsPlugin`](crate::SignalsPlugin); otherwise the closure is run
+    /// without batching.
+    fn batch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        if !begin_batch(self) {
+            return f(self);
+        }
+        let result = f(self);
+        end_batch(self);
+        result
+    }
+}
+
+impl<'p, 'w> BatchMutations for Ecx<'p, 'w> {
+    fn batch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.world.init_resource::<MutableBatch>();
+        begin_batch(&mut DeferredWorld::from(&mut *self.world));
+        let result = f(self);
+        end_batch(&mut DeferredWorld::from(&mut *self.world));
+        result
+    }
+}
+
+impl<'w, 's> BatchMutations for Commands<'w, 's> {
+    /// Run a closure as a batch. The batch covers all of the commands queued by the closure.
+    fn batch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.queue(|world: &mut World| {
+            world.init_resource::<MutableBatch>();
+            begin_batch(&mut DeferredWorld::from(world));
+        });
+        let result = f(self);
+        self.queue(|world: &mut World| end_batch(&mut DeferredWorld::from(world)));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CreateMutable, Rcx, TrackingScope};
+
+    #[test]
+    fn test_batch() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let a = world.create_mutable::<i32>(0);
+        let b = world.create_mutable::<i32>(0);
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        assert_eq!(a.get(&rcx) + b.get(&rcx), 0);
+        world.increment_change_tick();
+
+        // Writes are visible immediately, but not reported as changes until the batch ends.
+        world.batch(|world| {
+            a.set(world, 1);
+            world.batch(|world| b.set(world, 2));
+            assert_eq!(b.get(world), 2);
+            assert!(!scope.dependencies_changed(world, world.read_change_tick()));
+        });
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+
+        // Batches queued via commands cover the commands queued within them.
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        assert_eq!(a.get(&rcx) + b.get(&rcx), 3);
+        world.increment_change_tick();
+        world.commands().batch(|commands| {
+            commands.queue(move |world: &mut World| a.set(world, 5));
+            commands.queue(move |world: &mut World| {
+                assert!(is_batching(world));
+                b.set(world, 6);
+            });
+        });
+        world.flush();
+        assert!(!is_batching(&world));
+        assert_eq!(a.get(&world) + b.get(&world), 11);
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+    }
+}
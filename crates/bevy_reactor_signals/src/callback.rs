@@ -10,21 +10,22 @@ use bevy::{
 
 use crate::Ecx;
 
-/// Contains a reference to a callback. `P` is the type of the props.
+/// Contains a reference to a callback. `P` is the type of the props, and `R` is the type of
+/// the result.
 #[derive(PartialEq, Debug)]
-pub struct Callback<P: 'static = ()> {
-    pub(crate) id: SystemId<In<P>, ()>,
+pub struct Callback<P: 'static = (), R: 'static = ()> {
+    pub(crate) id: SystemId<In<P>, R>,
 }
 
-impl<P> Callback<P> {
+impl<P, R> Callback<P, R> {
     /// Construct a new callback
-    pub fn new(id: SystemId<In<P>, ()>) -> Self {
+    pub fn new(id: SystemId<In<P>, R>) -> Self {
         Self { id }
     }
 }
 
-impl<P> Copy for Callback<P> {}
-impl<P> Clone for Callback<P> {
+impl<P, R> Copy for Callback<P, R> {}
+impl<P, R> Clone for Callback<P, R> {
     fn clone(&self) -> Self {
         *self
     }
@@ -34,7 +35,7 @@ pub trait AnyCallback: 'static {
     fn remove(&self, world: &mut World);
 }
 
-impl<P: 'static> AnyCallback for Callback<P> {
+impl<P: 'static, R: 'static> AnyCallback for Callback<P, R> {
     fn remove(&self, world: &mut World) {
         // println!("Removing callback");
         world.remove_system(self.id).unwrap();
@@ -52,9 +53,14 @@ impl CallbackOwner {
     }
 
     /// Add an entry to the list of owned callbacks.
-    pub fn add<P: 'static>(&mut self, callback: Callback<P>) {
+    pub fn add<P: 'static, R: 'static>(&mut self, callback: Callback<P, R>) {
         self.0.push(Arc::new(callback));
     }
+
+    /// The number of owned callbacks.
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
 }
 
 pub(crate) fn cleanup_callbacks(world: &mut World) {
@@ -71,43 +77,139 @@ pub(crate) fn cleanup_callbacks(world: &mut World) {
 
 /// A trait for invoking callbacks.
 pub trait RunCallback {
+    /// The value returned when invoking a callback whose result is `R`. Contexts which run
+    /// callbacks immediately return the result; contexts which defer them return `()`.
+    type Output<R>;
+
     /// Invoke a callback with the given props.
-    fn run_callback<P: Send>(&mut self, callback: Callback<P>, props: P);
+    fn run_callback<P: Send, R: Send>(
+        &mut self,
+        callback: Callback<P, R>,
+        props: P,
+    ) -> Self::Output<R>;
 }
 
 /// A mutable reactive context. This allows write access to reactive data sources.
 impl RunCallback for World {
-    /// Invoke a callback with the given props.
+    type Output<R> = R;
+
+    /// Invoke a callback with the given props, returning the result.
     ///
     /// Arguments:
     /// * `callback` - The callback to invoke.
     /// * `props` - The props to pass to the callback.
-    fn run_callback<P>(&mut self, callback: Callback<P>, props: P) {
-        self.run_system_with_input(callback.id, props).unwrap();
+    fn run_callback<P, R>(&mut self, callback: Callback<P, R>, props: P) -> R {
+        self.run_system_with_input(callback.id, props).unwrap()
     }
 }
 
 /// A mutable reactive context. This allows write access to reactive data sources.
 impl<'w> RunCallback for DeferredWorld<'w> {
-    /// Invoke a callback with the given props.
+    type Output<R> = ();
+
+    /// Invoke a callback with the given props. The callback is run when commands are applied,
+    /// and its result is discarded; use [`RunCallbackThen`] to receive the result.
     ///
     /// Arguments:
     /// * `callback` - The callback to invoke.
     /// * `props` - The props to pass to the callback.
-    fn run_callback<P: Send>(&mut self, callback: Callback<P>, props: P) {
-        self.commands().run_system_with_input(callback.id, props);
+    fn run_callback<P: Send, R: Send>(&mut self, callback: Callback<P, R>, props: P) {
+        self.commands().run_callback(callback, props);
     }
 }
 
 impl<'p, 'w> RunCallback for Ecx<'p, 'w> {
-    fn run_callback<P: Send>(&mut self, callback: Callback<P>, props: P) {
-        self.world_mut().run_callback(callback, props);
+    type Output<R> = R;
+
+    fn run_callback<P: Send, R: Send>(&mut self, callback: Callback<P, R>, props: P) -> R {
+        self.world_mut().run_callback(callback, props)
     }
 }
 
 impl<'w, 's> RunCallback for Commands<'w, 's> {
-    fn run_callback<P: Send>(&mut self, callback: Callback<P>, props: P) {
-        self.run_system_with_input(callback.id, props)
+    type Output<R> = ();
+
+    /// Invoke a callback with the given props when the commands are applied. If the callback
+    /// has been unregistered by then, for example because its owner was despawned, a warning
+    /// is logged instead.
+    fn run_callback<P: Send, R: Send>(&mut self, callback: Callback<P, R>, props: P) {
+        self.queue(move |world: &mut World| {
+            run_deferred_callback(world, callback, props);
+        });
+    }
+}
+
+/// Run a callback from a queued command, logging a warning if it can no longer be run.
+fn run_deferred_callback<P, R>(world: &mut World, callback: Callback<P, R>, props: P) -> Option<R> {
+    world
+        .run_system_with_input(callback.id, props)
+        .map_err(|err| warn!("Deferred callback could not be run: {}", err))
+        .ok()
+}
+
+/// A trait for invoking callbacks and passing the result to a continuation. This works in
+/// contexts which defer running the callback, such as [`Commands`] and [`DeferredWorld`].
+pub trait RunCallbackThen {
+    /// Invoke a callback with the given props, and then call `then` with the result.
+    ///
+    /// Arguments:
+    /// * `callback` - The callback to invoke.
+    /// * `props` - The props to pass to the callback.
+    /// * `then` - Called with the result of the callback.
+    fn run_callback_then<P: Send, R: Send>(
+        &mut self,
+        callback: Callback<P, R>,
+        props: P,
+        then: impl FnOnce(R, &mut World) + Send + 'static,
+    );
+}
+
+impl RunCallbackThen for World {
+    fn run_callback_then<P: Send, R: Send>(
+        &mut self,
+        callback: Callback<P, R>,
+        props: P,
+        then: impl FnOnce(R, &mut World) + Send + 'static,
+    ) {
+        let result = self.run_callback(callback, props);
+        then(result, self);
+    }
+}
+
+impl<'w> RunCallbackThen for DeferredWorld<'w> {
+    fn run_callback_then<P: Send, R: Send>(
+        &mut self,
+        callback: Callback<P, R>,
+        props: P,
+        then: impl FnOnce(R, &mut World) + Send + 'static,
+    ) {
+        self.commands().run_callback_then(callback, props, then);
+    }
+}
+
+impl<'p, 'w> RunCallbackThen for Ecx<'p, 'w> {
+    fn run_callback_then<P: Send, R: Send>(
+        &mut self,
+        callback: Callback<P, R>,
+        props: P,
+        then: impl FnOnce(R, &mut World) + Send + 'static,
+    ) {
+        self.world_mut().run_callback_then(callback, props, then);
+    }
+}
+
+impl<'w, 's> RunCallbackThen for Commands<'w, 's> {
+    fn run_callback_then<P: Send, R: Send>(
+        &mut self,
+        callback: Callback<P, R>,
+        props: P,
+        then: impl FnOnce(R, &mut World) + Send + 'static,
+    ) {
+        self.queue(move |world: &mut World| {
+            if let Some(result) = run_deferred_callback(world, callback, props) {
+                then(result, world);
+            }
+        });
     }
 }
 
@@ -118,3 +220,50 @@ impl Command for UnregisterCallbackCmd {
         self.0.remove(world)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn test_callback_result() {
+        let mut world = World::default();
+        let id = world.register_system(|In(value): In<f32>| format!("{:.1}", value));
+        let format = Callback::new(id);
+        assert_eq!(world.run_callback(format, 0.25), "0.2");
+
+        // Deferred contexts deliver the result to a continuation.
+        let result = Arc::new(Mutex::new(None));
+        let result_ref = result.clone();
+        world
+            .commands()
+            .run_callback_then(format, 1.5, move |text, _world| {
+                *result_ref.lock().unwrap() = Some(text);
+            });
+        assert_eq!(*result.lock().unwrap(), None);
+        world.flush();
+        assert_eq!(result.lock().unwrap().as_deref(), Some("1.5"));
+    }
+
+    #[test]
+    fn test_deferred_callback_unregistered() {
+        let mut world = World::default();
+        let id = world.register_system(|In(value): In<i32>| value);
+        let callback = Callback::new(id);
+        let ran = Arc::new(Mutex::new(false));
+        let ran_ref = ran.clone();
+        world.commands().run_callback(callback, 1);
+        world
+            .commands()
+            .run_callback_then(callback, 2, move |_, _| {
+                *ran_ref.lock().unwrap() = true;
+            });
+        world.remove_system(id).unwrap();
+
+        // Callbacks which were unregistered before the commands ran are skipped.
+        world.flush();
+        assert!(!*ran.lock().unwrap());
+    }
+}
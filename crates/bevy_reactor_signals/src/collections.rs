@@ -0,0 +1,487 @@
+use std::{collections::VecDeque, hash::Hash};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{create_mutable, Mutable, ReadMutable, WriteMutable};
+
+/// The maximum number of operations retained by a collection's operation log. Readers which
+/// fall further behind than this see the collection as having been reset.
+const MAX_LOG_LEN: usize = 256;
+
+/// An operation which was applied to a [`MutableVec`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VecOp {
+    /// An item was appended to the end of the vector.
+    Push,
+    /// An item was inserted at the given index.
+    Insert(usize),
+    /// The item at the given index was removed.
+    Remove(usize),
+    /// The item at index `from` was removed and re-inserted at index `to`.
+    Move {
+        /// The index of the item before it was moved.
+        from: usize,
+        /// The index of the item after it was moved.
+        to: usize,
+    },
+    /// The item at the given index was replaced.
+    Set(usize),
+    /// All items were removed.
+    Clear,
+}
+
+/// An operation which was applied to a [`MutableMap`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapOp<K> {
+    /// The entry with the given key was inserted or replaced.
+    Insert(K),
+    /// The entry with the given key was removed.
+    Remove(K),
+    /// All entries were removed.
+    Clear,
+}
+
+/// The operations which have been applied to a collection since a given version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollectionChanges<Op> {
+    /// The current version of the collection, which should be passed to the next call to
+    /// `changes_since`.
+    pub version: u64,
+
+    /// The operations applied since the requested version, in order, or `None` if they are no
+    /// longer available, in which case the reader should treat the collection as new.
+    pub ops: Option<Vec<Op>>,
+}
+
+/// A bounded log of the operations applied to a collection.
+struct OpLog<Op> {
+    /// The version of the collection before the first operation in `ops` was applied.
+    start: u64,
+    ops: VecDeque<Op>,
+}
+
+impl<Op: Clone> OpLog<Op> {
+    fn version(&self) -> u64 {
+        self.start + self.ops.len() as u64
+    }
+
+    fn record(&mut self, op: Op) {
+        if self.ops.len() >= MAX_LOG_LEN {
+            self.ops.pop_front();
+            self.start += 1;
+        }
+        self.ops.push_back(op);
+    }
+
+    fn changes_since(&self, version: Option<u64>) -> CollectionChanges<Op> {
+        let ops = version
+            .filter(|v| *v >= self.start && *v <= self.version())
+            .map(|v| {
+                self.ops
+                    .range((v - self.start) as usize..)
+                    .cloned()
+                    .collect()
+            });
+        CollectionChanges {
+            version: self.version(),
+            ops,
+        }
+    }
+}
+
+impl<Op> Default for OpLog<Op> {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            ops: VecDeque::new(),
+        }
+    }
+}
+
+/// The contents of a [`MutableVec`].
+pub(crate) struct VecState<T> {
+    items: Vec<T>,
+    log: OpLog<VecOp>,
+}
+
+/// A reactive vector which records the operations applied to it, so that readers such as
+/// `for_each_vec` can update incrementally rather than comparing the old and new contents.
+/// Reading the vector from a reactive context adds the whole vector as a dependency.
+pub struct MutableVec<T> {
+    inner: Mutable<VecState<T>>,
+}
+
+impl<T> Copy for MutableVec<T> {}
+impl<T> Clone for MutableVec<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> PartialEq for MutableVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.id() == other.inner.id()
+    }
+}
+
+impl<T: Send + Sync + 'static> MutableVec<T> {
+    /// The entity that holds the vector.
+    pub fn id(&self) -> Entity {
+        self.inner.id()
+    }
+
+    /// Return the number of items in the vector.
+    pub fn len<R: ReadMutable>(&self, cx: &R) -> usize {
+        cx.read_mutable_map(&self.inner, |state| state.items.len())
+    }
+
+    /// Returns true if the vector has no items.
+    pub fn is_empty<R: ReadMutable>(&self, cx: &R) -> bool {
+        self.len(cx) == 0
+    }
+
+    /// Compute a value from the items of the vector.
+    pub fn map<R: ReadMutable, U, F: Fn(&[T]) -> U>(&self, cx: &R, f: F) -> U {
+        cx.read_mutable_map(&self.inner, |state| f(&state.items))
+    }
+
+    /// Return the operations which have been applied to the vector since `version`, or since
+    /// it was created if `version` is `None`.
+    pub fn changes_since<R: ReadMutable>(
+        &self,
+        cx: &R,
+        version: Option<u64>,
+    ) -> CollectionChanges<VecOp> {
+        cx.read_mutable_map(&self.inner, |state| state.log.changes_since(version))
+    }
+
+    /// Append an item to the end of the vector.
+    pub fn push<W: WriteMutable>(&self, cx: &mut W, value: T) {
+        self.update(cx, move |state| {
+            state.items.push(value);
+            VecOp::Push
+        });
+    }
+
+    /// Insert an item at `index`, shifting the following items along.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the vector.
+    pub fn insert<W: WriteMutable>(&self, cx: &mut W, index: usize, value: T) {
+        self.update(cx, move |state| {
+            state.items.insert(index, value);
+            VecOp::Insert(index)
+        });
+    }
+
+    /// Remove the item at `index`, shifting the following items back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove<W: WriteMutable>(&self, cx: &mut W, index: usize) {
+        self.update(cx, move |state| {
+            state.items.remove(index);
+            VecOp::Remove(index)
+        });
+    }
+
+    /// Move the item at index `from` so that it is at index `to`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    pub fn move_item<W: WriteMutable>(&self, cx: &mut W, from: usize, to: usize) {
+        self.update(cx, move |state| {
+            let item = state.items.remove(from);
+            state.items.insert(to, item);
+            VecOp::Move { from, to }
+        });
+    }
+
+    /// Replace the item at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set<W: WriteMutable>(&self, cx: &mut W, index: usize, value: T) {
+        self.update(cx, move |state| {
+            state.items[index] = value;
+            VecOp::Set(index)
+        });
+    }
+
+    /// Remove all items from the vector.
+    pub fn clear<W: WriteMutable>(&self, cx: &mut W) {
+        self.update(cx, |state| {
+            state.items.clear();
+            VecOp::Clear
+        });
+    }
+
+    fn update<W: WriteMutable, F: FnOnce(&mut VecState<T>) -> VecOp>(&self, cx: &mut W, f: F) {
+        cx.update_mutable(self.id(), |mut state: Mut<VecState<T>>| {
+            let op = f(&mut state);
+            state.log.record(op);
+        });
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> MutableVec<T> {
+    /// Return a clone of the item at `index`, or `None` if the index is out of bounds.
+    pub fn get<R: ReadMutable>(&self, cx: &R, index: usize) -> Option<T> {
+        cx.read_mutable_map(&self.inner, |state| state.items.get(index).cloned())
+    }
+
+    /// Return a clone of all of the items in the vector.
+    pub fn to_vec<R: ReadMutable>(&self, cx: &R) -> Vec<T> {
+        cx.read_mutable_map(&self.inner, |state| state.items.clone())
+    }
+}
+
+/// Create a new [`MutableVec`], owned by `parent`, with the given initial items.
+#[track_caller]
+pub fn create_mutable_vec<T: Send + Sync + 'static>(
+    world: &mut World,
+    parent: Entity,
+    init: Vec<T>,
+) -> MutableVec<T> {
+    MutableVec {
+        inner: create_mutable(
+            world,
+            parent,
+            VecState {
+                items: init,
+                log: OpLog::default(),
+            },
+        ),
+    }
+}
+
+/// The contents of a [`MutableMap`].
+pub(crate) struct MapState<K, V> {
+    entries: HashMap<K, V>,
+    log: OpLog<MapOp<K>>,
+}
+
+/// A reactive hash map which records the operations applied to it, so that readers can update
+/// incrementally. Reading the map from a reactive context adds the whole map as a dependency.
+pub struct MutableMap<K, V> {
+    inner: Mutable<MapState<K, V>>,
+}
+
+impl<K, V> Copy for MutableMap<K, V> {}
+impl<K, V> Clone for MutableMap<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> PartialEq for MutableMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.id() == other.inner.id()
+    }
+}
+
+impl<K, V> MutableMap<K, V>
+where
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Send + Sync + 'static,
+{
+    /// The entity that holds the map.
+    pub fn id(&self) -> Entity {
+        self.inner.id()
+    }
+
+    /// Return the number of entries in the map.
+    pub fn len<R: ReadMutable>(&self, cx: &R) -> usize {
+        cx.read_mutable_map(&self.inner, |state| state.entries.len())
+    }
+
+    /// Returns true if the map has no entries.
+    pub fn is_empty<R: ReadMutable>(&self, cx: &R) -> bool {
+        self.len(cx) == 0
+    }
+
+    /// Returns true if the map contains an entry for `key`.
+    pub fn contains_key<R: ReadMutable>(&self, cx: &R, key: &K) -> bool {
+        cx.read_mutable_map(&self.inner, |state| state.entries.contains_key(key))
+    }
+
+    /// Return the keys of the map, in arbitrary order.
+    pub fn keys<R: ReadMutable>(&self, cx: &R) -> Vec<K> {
+        cx.read_mutable_map(&self.inner, |state| state.entries.keys().cloned().collect())
+    }
+
+    /// Compute a value from the entries of the map.
+    pub fn map<R: ReadMutable, U, F: Fn(&HashMap<K, V>) -> U>(&self, cx: &R, f: F) -> U {
+        cx.read_mutable_map(&self.inner, |state| f(&state.entries))
+    }
+
+    /// Return the operations which have been applied to the map since `version`, or since it
+    /// was created if `version` is `None`.
+    pub fn changes_since<R: ReadMutable>(
+        &self,
+        cx: &R,
+        version: Option<u64>,
+    ) -> CollectionChanges<MapOp<K>> {
+        cx.read_mutable_map(&self.inner, |state| state.log.changes_since(version))
+    }
+
+    /// Insert an entry into the map, replacing any existing entry with the same key.
+    pub fn insert<W: WriteMutable>(&self, cx: &mut W, key: K, value: V) {
+        self.update(cx, move |state| {
+            state.entries.insert(key.clone(), value);
+            Some(MapOp::Insert(key))
+        });
+    }
+
+    /// Remove the entry for `key`, if there is one.
+    pub fn remove<W: WriteMutable>(&self, cx: &mut W, key: &K) {
+        self.update(cx, |state| {
+            state
+                .entries
+                .remove(key)
+                .map(|_| MapOp::Remove(key.clone()))
+        });
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear<W: WriteMutable>(&self, cx: &mut W) {
+        self.update(cx, |state| {
+            state.entries.clear();
+            Some(MapOp::Clear)
+        });
+    }
+
+    fn update<W: WriteMutable, F: FnOnce(&mut MapState<K, V>) -> Option<MapOp<K>>>(
+        &self,
+        cx: &mut W,
+        f: F,
+    ) {
+        cx.update_mutable(self.id(), |mut state: Mut<MapState<K, V>>| {
+            // Leave the map unchanged if the operation did nothing.
+            if let Some(op) = f(state.bypass_change_detection()) {
+                state.log.record(op);
+            }
+        });
+    }
+}
+
+impl<K, V> MutableMap<K, V>
+where
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Return a clone of the value for `key`, or `None` if there is no entry for `key`.
+    pub fn get<R: ReadMutable>(&self, cx: &R, key: &K) -> Option<V> {
+        cx.read_mutable_map(&self.inner, |state| state.entries.get(key).cloned())
+    }
+}
+
+/// Create a new [`MutableMap`], owned by `parent`, with the given initial entries.
+#[track_caller]
+pub fn create_mutable_map<K, V>(
+    world: &mut World,
+    parent: Entity,
+    init: HashMap<K, V>,
+) -> MutableMap<K, V>
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+{
+    MutableMap {
+        inner: create_mutable(
+            world,
+            parent,
+            MapState {
+                entries: init,
+                log: OpLog::default(),
+            },
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rcx, TrackingScope};
+
+    #[test]
+    fn test_mutable_vec() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let list = create_mutable_vec(&mut world, owner, vec!['a', 'b']);
+        let start = list.changes_since(&world, None);
+        assert_eq!(start.ops, None);
+
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        assert_eq!(list.len(&rcx), 2);
+        world.increment_change_tick();
+
+        list.push(&mut world, 'c');
+        list.insert(&mut world, 0, 'z');
+        list.move_item(&mut world, 3, 1);
+        list.set(&mut world, 2, 'y');
+        list.remove(&mut world, 3);
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+        assert_eq!(list.to_vec(&world), vec!['z', 'c', 'y']);
+
+        let changes = list.changes_since(&world, Some(start.version));
+        assert_eq!(
+            changes.ops,
+            Some(vec![
+                VecOp::Push,
+                VecOp::Insert(0),
+                VecOp::Move { from: 3, to: 1 },
+                VecOp::Set(2),
+                VecOp::Remove(3),
+            ])
+        );
+        assert_eq!(
+            list.changes_since(&world, Some(changes.version)).ops,
+            Some(vec![])
+        );
+
+        // Readers which fall too far behind must start over.
+        for _ in 0..=MAX_LOG_LEN {
+            list.set(&mut world, 0, 'x');
+        }
+        assert_eq!(list.changes_since(&world, Some(changes.version)).ops, None);
+    }
+
+    #[test]
+    fn test_mutable_map() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let map = create_mutable_map(&mut world, owner, HashMap::<&str, i32>::default());
+        let version = map.changes_since(&world, None).version;
+        map.insert(&mut world, "a", 1);
+        map.insert(&mut world, "b", 2);
+        map.insert(&mut world, "a", 3);
+        assert_eq!(map.len(&world), 2);
+        assert_eq!(map.get(&world, &"a"), Some(3));
+
+        // Removing a missing key does nothing.
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        assert!(map.contains_key(&rcx, &"b"));
+        world.increment_change_tick();
+        map.remove(&mut world, &"c");
+        assert!(!scope.dependencies_changed(&world, world.read_change_tick()));
+        map.remove(&mut world, &"b");
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+
+        assert_eq!(
+            map.changes_since(&world, Some(version)).ops,
+            Some(vec![
+                MapOp::Insert("a"),
+                MapOp::Insert("b"),
+                MapOp::Insert("a"),
+                MapOp::Remove("b"),
+            ])
+        );
+    }
+}
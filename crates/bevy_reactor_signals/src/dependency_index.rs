@@ -0,0 +1,395 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    ecs::{
+        component::{ComponentId, Tick},
+        schedule::InternedScheduleLabel,
+        world::DeferredWorld,
+    },
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+/// The set of keys that a single tracking scope is subscribed to.
+#[derive(Default)]
+pub(crate) struct Subscription {
+    pub(crate) components: Vec<(Entity, ComponentId)>,
+    pub(crate) resources: Vec<ComponentId>,
+
+    /// True if the scope has dependencies which must be polled on every pass.
+    pub(crate) polled: bool,
+
+    /// True if the scope wants to be re-run at the start of the next frame.
+    pub(crate) deferred: bool,
+
+    /// True if the scope has been explicitly marked as changed.
+    pub(crate) changed: bool,
+
+    /// The schedule the scope reacts in, or `None` for the default schedule.
+    pub(crate) schedule: Option<InternedScheduleLabel>,
+
+    /// The length of the longest chain of reactions that this scope depends on, computed
+    /// when the scope is subscribed.
+    pub(crate) depth: u32,
+}
+
+/// Central index which maps each dependency (a component on an entity, or a resource) to the
+/// set of tracking scopes that are subscribed to it. Rather than asking every tracking scope
+/// whether any of its dependencies have changed, we check each distinct dependency once, and
+/// only examine the scopes that subscribe to the dependencies which actually changed.
+///
+/// The index is kept up to date by the component hooks on [`TrackingScope`], and by
+/// `run_reactions` whenever a reaction produces a new set of dependencies.
+///
+/// Writes to mutables are reported to the index as they happen (see [`record_write`]). Once a
+/// write to a cell has been reported, its subscribers are found without looking at its change
+/// ticks. Other components and resources, which can be modified by any system, have no such
+/// write hook; for these, and for cells which have not been written yet, the index falls back
+/// to scanning the change ticks of the dependency on each pass.
+///
+/// [`TrackingScope`]: crate::TrackingScope
+#[derive(Resource, Default)]
+pub(crate) struct DependencyIndex {
+    /// Subscribers for each component dependency, grouped by entity so that each entity only
+    /// needs to be looked up once per scan.
+    components: HashMap<Entity, HashMap<ComponentId, HashSet<Entity>>>,
+
+    /// Subscribers for each resource dependency.
+    resources: HashMap<ComponentId, HashSet<Entity>>,
+
+    /// Scopes that have polled dependencies, which are checked on every pass.
+    polled: HashSet<Entity>,
+
+    /// Scopes that have requested to be re-run at the start of the next frame.
+    deferred: HashSet<Entity>,
+
+    /// Scopes which need to be checked on the next pass regardless of which dependencies
+    /// changed, such as scopes which have just been inserted.
+    pending: HashSet<Entity>,
+
+    /// Scopes which have been marked as changed via [`TrackingScope::set_changed`].
+    ///
+    /// [`TrackingScope::set_changed`]: crate::TrackingScope::set_changed
+    pub(crate) flagged: Arc<Mutex<Vec<Entity>>>,
+
+    /// Component dependencies which have been written since the previous pass.
+    written: HashSet<(Entity, ComponentId)>,
+
+    /// Components whose writes are reported via [`record_write`], and which therefore don't
+    /// need to be scanned. Entries are removed when the component is removed.
+    write_tracked: HashSet<(Entity, ComponentId)>,
+
+    /// Subscribed component dependencies whose change ticks must be scanned on each pass,
+    /// which is every component dependency that isn't in `write_tracked`.
+    scanned: HashMap<Entity, HashSet<ComponentId>>,
+
+    /// The current subscription for each scope, used to unsubscribe.
+    subscriptions: HashMap<Entity, Subscription>,
+
+    /// The tick of the previous scan, for each schedule that reactions are run in.
+    last_scan: HashMap<Option<InternedScheduleLabel>, Tick>,
+}
+
+impl DependencyIndex {
+    /// Subscribe a tracking scope to a set of dependencies, replacing any previous
+    /// subscription. If `fresh` is true, the scope will be checked on the next pass even if
+    /// none of its dependencies are known to have changed.
+    pub(crate) fn subscribe(&mut self, scope: Entity, mut subscription: Subscription, fresh: bool) {
+        self.unsubscribe(scope);
+        // A scope which reads the output of another reaction (such as a memo) must run
+        // after that reaction.
+        subscription.depth = subscription
+            .components
+            .iter()
+            .filter_map(|(entity, _)| self.subscriptions.get(entity))
+            .map(|upstream| upstream.depth + 1)
+            .max()
+            .unwrap_or(0);
+        for (entity, component) in subscription.components.iter() {
+            self.components
+                .entry(*entity)
+                .or_default()
+                .entry(*component)
+                .or_default()
+                .insert(scope);
+            if !self.write_tracked.contains(&(*entity, *component)) {
+                self.scanned.entry(*entity).or_default().insert(*component);
+            }
+        }
+        for resource in subscription.resources.iter() {
+            self.resources.entry(*resource).or_default().insert(scope);
+        }
+        if subscription.polled {
+            self.polled.insert(scope);
+        }
+        if subscription.deferred {
+            self.deferred.insert(scope);
+        }
+        if fresh || subscription.changed {
+            self.pending.insert(scope);
+        }
+        self.subscriptions.insert(scope, subscription);
+    }
+
+    /// Remove all of the subscriptions for a tracking scope.
+    pub(crate) fn unsubscribe(&mut self, scope: Entity) {
+        let Some(subscription) = self.subscriptions.remove(&scope) else {
+            return;
+        };
+        for (entity, component) in subscription.components.iter() {
+            if let Some(by_component) = self.components.get_mut(entity) {
+                if let Some(subscribers) = by_component.get_mut(component) {
+                    subscribers.remove(&scope);
+                    if subscribers.is_empty() {
+                        by_component.remove(component);
+                        if let Some(scanned) = self.scanned.get_mut(entity) {
+                            scanned.remove(component);
+                            if scanned.is_empty() {
+                                self.scanned.remove(entity);
+                            }
+                        }
+                    }
+                }
+                if by_component.is_empty() {
+                    self.components.remove(entity);
+                }
+            }
+        }
+        for resource in subscription.resources.iter() {
+            if let Some(subscribers) = self.resources.get_mut(resource) {
+                subscribers.remove(&scope);
+                if subscribers.is_empty() {
+                    self.resources.remove(resource);
+                }
+            }
+        }
+        self.polled.remove(&scope);
+        self.deferred.remove(&scope);
+        self.pending.remove(&scope);
+    }
+
+    /// The dependency depth of a scope. Scopes with a lower depth should be run first.
+    pub(crate) fn depth(&self, scope: Entity) -> u32 {
+        self.subscriptions.get(&scope).map_or(0, |s| s.depth)
+    }
+
+    /// The schedule that a scope reacts in, or `None` for the default schedule.
+    pub(crate) fn schedule(&self, scope: Entity) -> Option<InternedScheduleLabel> {
+        self.subscriptions.get(&scope).and_then(|s| s.schedule)
+    }
+
+    /// Return the scopes which subscribe to any of the components of the given entity.
+    pub(crate) fn subscribers(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.components
+            .get(&entity)
+            .into_iter()
+            .flat_map(|by_component| by_component.values())
+            .flatten()
+            .copied()
+    }
+
+    /// Return the set of scopes in the given schedule which may need to react, in entity
+    /// order. This is a superset of the scopes whose dependencies have actually changed; the
+    /// caller is expected to check each candidate individually.
+    ///
+    /// Arguments:
+    /// * `world` - The world to check for changes.
+    /// * `this_run` - The tick of the current pass.
+    /// * `schedule` - The schedule being run, or `None` for the default schedule.
+    /// * `include_deferred` - Whether to include scopes which have requested a deferred change.
+    pub(crate) fn candidates(
+        &mut self,
+        world: &World,
+        this_run: Tick,
+        schedule: Option<InternedScheduleLabel>,
+        include_deferred: bool,
+    ) -> Vec<Entity> {
+        // Reactions can write to their dependencies during the same tick as the previous scan,
+        // so the window of changes we look at includes the tick of the previous scan.
+        let last_scan = self
+            .last_scan
+            .insert(schedule, this_run)
+            .unwrap_or_default();
+        let since = Tick::new(last_scan.get().wrapping_sub(1));
+        self.pending.extend(self.flagged.lock().unwrap().drain(..));
+        for (entity, component) in std::mem::take(&mut self.written) {
+            if let Some(subscribers) = self
+                .components
+                .get(&entity)
+                .and_then(|by_component| by_component.get(&component))
+            {
+                self.pending.extend(subscribers.iter().copied());
+            }
+        }
+        let mut result: HashSet<Entity> = HashSet::default();
+        result.extend(self.polled.iter().copied());
+        if include_deferred {
+            result.extend(self.deferred.iter().copied());
+        }
+
+        for (entity, components) in self.scanned.iter() {
+            let Ok(entity_ref) = world.get_entity(*entity) else {
+                continue;
+            };
+            for component in components.iter() {
+                if entity_ref
+                    .get_change_ticks_by_id(*component)
+                    .is_some_and(|ct| ct.is_changed(since, this_run))
+                {
+                    if let Some(subscribers) = self
+                        .components
+                        .get(entity)
+                        .and_then(|by_component| by_component.get(component))
+                    {
+                        result.extend(subscribers.iter().copied());
+                    }
+                }
+            }
+        }
+
+        for (resource, subscribers) in self.resources.iter() {
+            if world
+                .get_resource_change_ticks_by_id(*resource)
+                .is_some_and(|ct| ct.is_changed(since, this_run))
+            {
+                result.extend(subscribers.iter().copied());
+            }
+        }
+
+        // Pending scopes which belong to other schedules stay pending until that schedule runs.
+        result.retain(|scope| self.schedule(*scope) == schedule);
+        self.pending.retain(|scope| {
+            if self.subscriptions.get(scope).and_then(|s| s.schedule) == schedule {
+                result.insert(*scope);
+                false
+            } else {
+                true
+            }
+        });
+
+        let mut result: Vec<Entity> = result.into_iter().collect();
+        result.sort_unstable();
+        result
+    }
+}
+
+/// Report a write to a component whose writes are always reported, such as the cell of a
+/// mutable. Once a write to a component on an entity has been reported, the index no longer
+/// scans the change ticks of that component, so every subsequent write to it must be reported
+/// as well. The same component on other entities is unaffected.
+pub(crate) fn record_write(world: &mut DeferredWorld, entity: Entity, component: ComponentId) {
+    if let Some(mut index) = world.get_resource_mut::<DependencyIndex>() {
+        index.written.insert((entity, component));
+        if index.write_tracked.insert((entity, component)) {
+            if let Some(scanned) = index.scanned.get_mut(&entity) {
+                scanned.remove(&component);
+                if scanned.is_empty() {
+                    index.scanned.remove(&entity);
+                }
+            }
+        }
+    }
+}
+
+/// Component hook which forgets that writes to a component were reported, once the component
+/// has been removed.
+pub(crate) fn forget_write_tracking(
+    mut world: DeferredWorld,
+    entity: Entity,
+    component: ComponentId,
+) {
+    if let Some(mut index) = world.get_resource_mut::<DependencyIndex>() {
+        index.write_tracked.remove(&(entity, component));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use super::*;
+    use crate::{
+        create_mutable, mutable::MutableCell, Mutable, Rcx, Reaction, ReactionCell, SignalsPlugin,
+        TrackingScope,
+    };
+
+    #[test]
+    fn test_mutable_writes_are_recorded() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin);
+        let world = app.world_mut();
+        let owner = world.spawn_empty().id();
+        let mutable = create_mutable(world, owner, 0);
+        let mut scope = TrackingScope::new(world.change_tick());
+        mutable.get(&Rcx::new(world, owner, &mut scope));
+        let scope_entity = world.spawn(scope).id();
+        app.update();
+
+        let world = app.world_mut();
+        mutable.set(world, 1);
+        let component = mutable.component_id(world).unwrap();
+        let index = world.resource::<DependencyIndex>();
+        assert!(index.write_tracked.contains(&(mutable.id(), component)));
+        assert!(index.written.contains(&(mutable.id(), component)));
+
+        let this_run = world.change_tick();
+        let candidates = world.resource_scope(|world, mut index: Mut<DependencyIndex>| {
+            index.candidates(world, this_run, None, false)
+        });
+        assert_eq!(candidates, vec![scope_entity]);
+        assert!(world.resource::<DependencyIndex>().written.is_empty());
+    }
+
+    /// Reaction which copies the value of a mutable into an atomic.
+    struct Mirror(Mutable<i32>, Arc<AtomicI32>);
+
+    impl Reaction for Mirror {
+        fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+            let value = self.0.get(&Rcx::new(world, owner, tracking));
+            self.1.store(value, Ordering::Relaxed);
+        }
+    }
+
+    fn mirror(world: &mut World, mutable: Mutable<i32>) -> Arc<AtomicI32> {
+        let seen = Arc::new(AtomicI32::new(0));
+        let mut reaction = Mirror(mutable, seen.clone());
+        let owner = world.spawn_empty().id();
+        let mut scope = TrackingScope::new(world.change_tick());
+        reaction.react(owner, world, &mut scope);
+        world
+            .entity_mut(owner)
+            .insert((scope, ReactionCell::new(reaction)));
+        seen
+    }
+
+    #[test]
+    fn test_unreported_writes_are_scanned() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin);
+        let world = app.world_mut();
+        let owner = world.spawn_empty().id();
+        let reported = create_mutable(world, owner, 0);
+        let unreported = create_mutable(world, owner, 0);
+        let seen_reported = mirror(world, reported);
+        let seen_unreported = mirror(world, unreported);
+        app.update();
+
+        reported.set(app.world_mut(), 1);
+        app.update();
+        assert_eq!(seen_reported.load(Ordering::Relaxed), 1);
+
+        // Reporting a write to one cell doesn't stop the index from scanning other cells of
+        // the same type, so a write which bypasses `record_write` is still noticed.
+        app.world_mut()
+            .get_mut::<MutableCell<i32>>(unreported.id())
+            .unwrap()
+            .0 = 2;
+        app.update();
+        assert_eq!(seen_unreported.load(Ordering::Relaxed), 2);
+
+        // Despawning a cell forgets that its writes were reported.
+        let world = app.world_mut();
+        world.despawn(reported.id());
+        assert!(world.resource::<DependencyIndex>().write_tracked.is_empty());
+    }
+}
@@ -1,8 +1,23 @@
-use std::{marker::PhantomData, sync::Arc};
+use std::{
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    thread::ThreadId,
+};
 
-use bevy::{ecs::world::DeferredWorld, prelude::*, ui::experimental::GhostNode};
+use bevy::{
+    ecs::{
+        component::{ComponentId, Tick},
+        world::DeferredWorld,
+    },
+    prelude::*,
+};
 
-use crate::{Rcx, TrackingScope};
+use crate::{
+    diagnostics::DerivedMarker,
+    error::{SignalError, SignalErrorKind, SignalOrigin},
+    tracking_scope::{Cleanup, RetiredCleanups},
+    Rcx, TrackingScope,
+};
 
 pub(crate) trait DerivedFnRef<R> {
     fn call(&self, rcx: &mut Rcx) -> R;
@@ -16,6 +31,7 @@ impl<R, F: Fn(&mut Rcx) -> R> DerivedFnRef<R> for F {
 
 /// Contains a boxed, type-erased function which returns a reactive result.
 #[derive(Component)]
+#[require(DerivedMarker)]
 pub struct DerivedCell<R>(pub(crate) Arc<dyn DerivedFnRef<R> + Send + Sync>);
 
 impl<R> DerivedCell<R> {
@@ -25,10 +41,146 @@ impl<R> DerivedCell<R> {
     }
 }
 
+/// The result of the most recent computation of a cached derived, along with the dependencies
+/// that were accessed while computing it.
+struct DerivedCache<R> {
+    value: Arc<R>,
+    scope: TrackingScope,
+}
+
+/// Like [`DerivedCell`], but retains the result of the previous computation. The compute
+/// function is only called again when one of the dependencies of the cached value has changed.
+#[derive(Component)]
+#[require(DerivedMarker)]
+#[component(on_remove = remove_cached_derived::<R>)]
+pub struct CachedDerivedCell<R: Send + Sync + 'static> {
+    compute: Arc<dyn DerivedFnRef<R> + Send + Sync>,
+    cache: Mutex<Option<DerivedCache<R>>>,
+
+    /// The threads which are currently computing the value, used to detect a computation
+    /// which reads its own result.
+    computing: Mutex<Vec<ThreadId>>,
+
+    /// Cleanups of previous computations which could not be handed to [`RetiredCleanups`].
+    retired: Mutex<Vec<Cleanup>>,
+}
+
+impl<R: Send + Sync + 'static> CachedDerivedCell<R> {
+    /// Construct a new `CachedDerivedCell` from a function.
+    pub fn new<F: Send + Sync + 'static + Fn(&mut Rcx) -> R>(f: F) -> Self {
+        Self {
+            compute: Arc::new(f),
+            cache: Mutex::new(None),
+            computing: Mutex::new(Vec::new()),
+            retired: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Read the cached value, recomputing it first if it is out of date. The dependencies of
+    /// the cached value are added to the caller's tracking scope, so that the caller will
+    /// react when the derived would produce a different result.
+    ///
+    /// The cache is not locked while the value is computed or passed to `f`, so other readers
+    /// are not blocked by a slow computation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the computation reads the derived itself, directly or indirectly.
+    fn read<U, F: FnOnce(&R) -> U>(
+        &self,
+        world: &World,
+        owner: Entity,
+        scope: &mut TrackingScope,
+        f: F,
+    ) -> U {
+        let tick = world.read_change_tick();
+        if let Some(entry) = self.cache.lock().unwrap().as_ref() {
+            if !entry.scope.has_deferred_change() && !entry.scope.dependencies_changed(world, tick)
+            {
+                scope.copy_deps(&entry.scope);
+                let value = entry.value.clone();
+                return f(&value);
+            }
+        }
+
+        // Changes made during the current tick can't be distinguished from changes that
+        // happened before the computation, so treat the cache as valid only for changes
+        // strictly before this tick.
+        let mut cache_scope = TrackingScope::new(Tick::new(tick.get().wrapping_sub(1)));
+        let value = {
+            let _guard = ComputeGuard::enter::<R>(&self.computing);
+            Arc::new(
+                self.compute
+                    .call(&mut Rcx::new(world, owner, &mut cache_scope)),
+            )
+        };
+        scope.copy_deps(&cache_scope);
+        let previous = self.cache.lock().unwrap().replace(DerivedCache {
+            value: value.clone(),
+            scope: cache_scope,
+        });
+        // The world can't be mutated here, so the cleanups of the previous computation are
+        // run later, either by the next pass of reactions or when the cell is removed.
+        if let Some(previous) = previous {
+            match world.get_resource::<RetiredCleanups>() {
+                Some(retired) => retired.push(previous.scope.cleanups),
+                None => self.retired.lock().unwrap().extend(previous.scope.cleanups),
+            }
+        }
+        f(&value)
+    }
+}
+
+/// Records that the current thread is computing a cached derived, for as long as it is alive.
+struct ComputeGuard<'a> {
+    computing: &'a Mutex<Vec<ThreadId>>,
+    thread: ThreadId,
+}
+
+impl<'a> ComputeGuard<'a> {
+    fn enter<R>(computing: &'a Mutex<Vec<ThreadId>>) -> Self {
+        let thread = std::thread::current().id();
+        let mut threads = computing.lock().unwrap();
+        if threads.contains(&thread) {
+            drop(threads);
+            panic!(
+                "Cycle detected: cached derived {} was read while computing its own value",
+                std::any::type_name::<R>()
+            );
+        }
+        threads.push(thread);
+        Self { computing, thread }
+    }
+}
+
+impl<'a> Drop for ComputeGuard<'a> {
+    fn drop(&mut self) {
+        let mut threads = self.computing.lock().unwrap();
+        threads.retain(|thread| *thread != self.thread);
+    }
+}
+
+/// Run the cleanups of the current and any retired computations of a cached derived.
+fn remove_cached_derived<R: Send + Sync + 'static>(
+    mut world: DeferredWorld,
+    entity: Entity,
+    _component: ComponentId,
+) {
+    let mut cell = world.get_mut::<CachedDerivedCell<R>>(entity).unwrap();
+    let mut cleanups = std::mem::take(cell.retired.get_mut().unwrap());
+    if let Some(entry) = cell.cache.get_mut().unwrap().as_mut() {
+        cleanups.append(&mut entry.scope.cleanups);
+    }
+    for cleanup_fn in cleanups {
+        cleanup_fn(&mut world);
+    }
+}
+
 /// A [`Derived`] is a readonly value that is computed from other signals.
 #[derive(PartialEq)]
 pub struct Derived<R> {
     pub(crate) id: Entity,
+    pub(crate) origin: SignalOrigin,
     pub(crate) marker: std::marker::PhantomData<R>,
 }
 
@@ -37,6 +189,62 @@ impl<R> Derived<R> {
     pub fn id(&self) -> Entity {
         self.id
     }
+
+    /// Construct an error describing why this derived could not be read.
+    pub(crate) fn error(&self, kind: SignalErrorKind) -> SignalError {
+        SignalError::new::<Self>(kind, self.id, self.origin)
+    }
+}
+
+impl<R> Derived<R>
+where
+    R: Send + Sync + 'static,
+{
+    /// Read the value of this [`Derived`] using a mapping function, returning an error if the
+    /// derived has been despawned.
+    ///
+    /// # Panics
+    ///
+    /// Only the derived itself is checked. If the compute function reads a signal which has
+    /// been despawned using a panicking accessor such as `get`, this still panics; compute
+    /// functions which may outlive their inputs should read them with `try_get`.
+    pub fn try_map<C: ReadDerived, U, F: FnOnce(&R) -> U>(
+        &self,
+        cx: &C,
+        f: F,
+    ) -> Result<U, SignalError> {
+        cx.try_read_derived_map(self, f)
+    }
+}
+
+impl<R> Derived<R>
+where
+    R: Copy + Send + Sync + 'static,
+{
+    /// Get the value of this [`Derived`] with Copy semantics, returning an error if the
+    /// derived has been despawned.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`try_map`](Self::try_map).
+    pub fn try_get<C: ReadDerived>(&self, cx: &C) -> Result<R, SignalError> {
+        cx.try_read_derived_map(self, |value| *value)
+    }
+}
+
+impl<R> Derived<R>
+where
+    R: Clone + Send + Sync + 'static,
+{
+    /// Get the value of this [`Derived`] with Clone semantics, returning an error if the
+    /// derived has been despawned.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`try_map`](Self::try_map).
+    pub fn try_get_clone<C: ReadDerived>(&self, cx: &C) -> Result<R, SignalError> {
+        cx.try_read_derived_map(self, R::clone)
+    }
 }
 
 impl<T> Copy for Derived<T> {}
@@ -65,6 +273,17 @@ pub trait ReadDerived {
     fn read_derived_map<R, U, F: Fn(&R) -> U>(&self, derived: &Derived<R>, f: F) -> U
     where
         R: Send + Sync + 'static;
+
+    /// Read the value of a derived signal using a mapping function, returning an error if the
+    /// derived has been despawned. This adds any dependencies of the derived signal to the
+    /// current tracking scope.
+    fn try_read_derived_map<R, U, F: FnOnce(&R) -> U>(
+        &self,
+        derived: &Derived<R>,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        R: Send + Sync + 'static;
 }
 
 /// Trait used to implement reading of derives while passing an explicit tracking scope.
@@ -91,6 +310,18 @@ pub(crate) trait ReadDerivedInternal {
     ) -> U
     where
         R: Send + Sync + 'static;
+
+    /// Read the value of a derived signal using a mapping function, returning an error if the
+    /// derived has been despawned. This adds any dependencies of the derived signal to the
+    /// current tracking scope.
+    fn try_read_derived_map_with_scope<R, U, F: FnOnce(&R) -> U>(
+        &self,
+        derived: &Derived<R>,
+        scope: &mut TrackingScope,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        R: Send + Sync + 'static;
 }
 
 impl ReadDerived for World {
@@ -120,6 +351,19 @@ impl ReadDerived for World {
         let mut scope = TrackingScope::new(ticks);
         self.read_derived_map_with_scope(derived.id, &mut scope, f)
     }
+
+    fn try_read_derived_map<R, U, F: FnOnce(&R) -> U>(
+        &self,
+        derived: &Derived<R>,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        R: Send + Sync + 'static,
+    {
+        let ticks = self.read_change_tick();
+        let mut scope = TrackingScope::new(ticks);
+        self.try_read_derived_map_with_scope(derived, &mut scope, f)
+    }
 }
 
 impl ReadDerivedInternal for World {
@@ -127,30 +371,14 @@ impl ReadDerivedInternal for World {
     where
         R: Send + Sync + Copy + 'static,
     {
-        let derived_entity = self.entity(derived);
-        match derived_entity.get::<DerivedCell<R>>() {
-            Some(cell) => {
-                let derived_fn = cell.0.clone();
-                let mut rcx = Rcx::new(self, derived, scope);
-                derived_fn.call(&mut rcx)
-            }
-            _ => panic!("No derived found for {:?}", derived),
-        }
+        read_derived_value(self, derived, scope, |value: &R| *value)
     }
 
     fn read_derived_clone_with_scope<R>(&self, derived: Entity, scope: &mut TrackingScope) -> R
     where
         R: Send + Sync + Clone + 'static,
     {
-        let derived_entity = self.entity(derived);
-        match derived_entity.get::<DerivedCell<R>>() {
-            Some(cell) => {
-                let derived_fn = cell.0.clone();
-                let mut rcx = Rcx::new(self, derived, scope);
-                derived_fn.call(&mut rcx).clone()
-            }
-            _ => panic!("No derived found for {:?}", derived),
-        }
+        read_derived_value(self, derived, scope, |value: &R| value.clone())
     }
 
     fn read_derived_map_with_scope<R, U, F: Fn(&R) -> U>(
@@ -162,15 +390,19 @@ impl ReadDerivedInternal for World {
     where
         R: Send + Sync + 'static,
     {
-        let derived_entity = self.entity(derived);
-        match derived_entity.get::<DerivedCell<R>>() {
-            Some(cell) => {
-                let derived_fn = cell.0.clone();
-                let mut rcx = Rcx::new(self, derived, scope);
-                f(&derived_fn.call(&mut rcx))
-            }
-            _ => panic!("No derived found for {:?}", derived),
-        }
+        read_derived_value(self, derived, scope, f)
+    }
+
+    fn try_read_derived_map_with_scope<R, U, F: FnOnce(&R) -> U>(
+        &self,
+        derived: &Derived<R>,
+        scope: &mut TrackingScope,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        R: Send + Sync + 'static,
+    {
+        try_read_derived_value(self, derived.id, scope, f).map_err(|kind| derived.error(kind))
     }
 }
 
@@ -201,6 +433,19 @@ impl<'w> ReadDerived for DeferredWorld<'w> {
         let mut scope = TrackingScope::new(ticks);
         self.read_derived_map_with_scope(derived.id, &mut scope, f)
     }
+
+    fn try_read_derived_map<R, U, F: FnOnce(&R) -> U>(
+        &self,
+        derived: &Derived<R>,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        R: Send + Sync + 'static,
+    {
+        let ticks = self.read_change_tick();
+        let mut scope = TrackingScope::new(ticks);
+        self.try_read_derived_map_with_scope(derived, &mut scope, f)
+    }
 }
 
 impl<'w> ReadDerivedInternal for DeferredWorld<'w> {
@@ -208,30 +453,14 @@ impl<'w> ReadDerivedInternal for DeferredWorld<'w> {
     where
         R: Send + Sync + Copy + 'static,
     {
-        let derived_entity = self.entity(derived);
-        match derived_entity.get::<DerivedCell<R>>() {
-            Some(cell) => {
-                let derived_fn = cell.0.clone();
-                let mut rcx = Rcx::new(self, derived, scope);
-                derived_fn.call(&mut rcx)
-            }
-            _ => panic!("No derived found for {:?}", derived),
-        }
+        read_derived_value(self, derived, scope, |value: &R| *value)
     }
 
     fn read_derived_clone_with_scope<R>(&self, derived: Entity, scope: &mut TrackingScope) -> R
     where
         R: Send + Sync + Clone + 'static,
     {
-        let derived_entity = self.entity(derived);
-        match derived_entity.get::<DerivedCell<R>>() {
-            Some(cell) => {
-                let derived_fn = cell.0.clone();
-                let mut rcx = Rcx::new(self, derived, scope);
-                derived_fn.call(&mut rcx).clone()
-            }
-            _ => panic!("No derived found for {:?}", derived),
-        }
+        read_derived_value(self, derived, scope, |value: &R| value.clone())
     }
 
     fn read_derived_map_with_scope<R, U, F: Fn(&R) -> U>(
@@ -243,28 +472,222 @@ impl<'w> ReadDerivedInternal for DeferredWorld<'w> {
     where
         R: Send + Sync + 'static,
     {
-        let derived_entity = self.entity(derived);
-        match derived_entity.get::<DerivedCell<R>>() {
-            Some(cell) => {
-                let derived_fn = cell.0.clone();
-                let mut rcx = Rcx::new(self, derived, scope);
-                f(&derived_fn.call(&mut rcx))
-            }
-            _ => panic!("No derived found for {:?}", derived),
-        }
+        read_derived_value(self, derived, scope, f)
+    }
+
+    fn try_read_derived_map_with_scope<R, U, F: FnOnce(&R) -> U>(
+        &self,
+        derived: &Derived<R>,
+        scope: &mut TrackingScope,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        R: Send + Sync + 'static,
+    {
+        try_read_derived_value(self, derived.id, scope, f).map_err(|kind| derived.error(kind))
+    }
+}
+
+/// Compute (or fetch from the cache) the value of a derived, adding its dependencies to `scope`,
+/// and pass the result to `f`.
+fn read_derived_value<R, U, F: FnOnce(&R) -> U>(
+    world: &World,
+    derived: Entity,
+    scope: &mut TrackingScope,
+    f: F,
+) -> U
+where
+    R: Send + Sync + 'static,
+{
+    match try_read_derived_value(world, derived, scope, f) {
+        Ok(value) => value,
+        Err(_) => panic!("No derived found for {:?}", derived),
+    }
+}
+
+/// Like [`read_derived_value`], but returns the reason the derived could not be read instead
+/// of panicking.
+fn try_read_derived_value<R, U, F: FnOnce(&R) -> U>(
+    world: &World,
+    derived: Entity,
+    scope: &mut TrackingScope,
+    f: F,
+) -> Result<U, SignalErrorKind>
+where
+    R: Send + Sync + 'static,
+{
+    let Ok(derived_entity) = world.get_entity(derived) else {
+        return Err(SignalErrorKind::Despawned);
+    };
+    if let Some(cell) = derived_entity.get::<DerivedCell<R>>() {
+        let derived_fn = cell.0.clone();
+        let mut rcx = Rcx::new(world, derived, scope);
+        Ok(f(&derived_fn.call(&mut rcx)))
+    } else if let Some(cell) = derived_entity.get::<CachedDerivedCell<R>>() {
+        Ok(cell.read(world, derived, scope, f))
+    } else {
+        Err(SignalErrorKind::WrongType)
     }
 }
 
 /// Helper function for creating deriveds.
+#[track_caller]
 pub fn create_derived<R: 'static, F: Send + Sync + 'static + Fn(&mut Rcx) -> R>(
     world: &mut World,
     compute: F,
 ) -> Derived<R> {
-    let derived = world
-        .spawn((DerivedCell::new(compute), GhostNode::default()))
-        .id();
+    let derived = world.spawn(DerivedCell::new(compute)).id();
+    Derived {
+        id: derived,
+        origin: SignalOrigin::caller(),
+        marker: PhantomData,
+    }
+}
+
+/// Helper function for creating cached deriveds. Unlike [`create_derived`], the result of the
+/// computation is retained, and the compute function is only called again when one of its
+/// dependencies changes. Readers of the cached derived still inherit its dependencies.
+///
+/// Cleanup functions registered by the compute function are run after the value is
+/// recomputed, or when the derived is despawned.
+#[track_caller]
+pub fn create_cached_derived<
+    R: Send + Sync + 'static,
+    F: Send + Sync + 'static + Fn(&mut Rcx) -> R,
+>(
+    world: &mut World,
+    compute: F,
+) -> Derived<R> {
+    let derived = world.spawn(CachedDerivedCell::new(compute)).id();
     Derived {
         id: derived,
+        origin: SignalOrigin::caller(),
         marker: PhantomData,
     }
 }
+
+/// Trait for creating new deriveds.
+pub trait CreateDerived {
+    /// Create a new [`Derived`]. See [`create_derived`].
+    fn create_derived<R: 'static, F: Send + Sync + 'static + Fn(&mut Rcx) -> R>(
+        &mut self,
+        compute: F,
+    ) -> Derived<R>;
+}
+
+impl CreateDerived for World {
+    #[track_caller]
+    fn create_derived<R: 'static, F: Send + Sync + 'static + Fn(&mut Rcx) -> R>(
+        &mut self,
+        compute: F,
+    ) -> Derived<R> {
+        create_derived(self, compute)
+    }
+}
+
+impl<'w, 's> CreateDerived for Commands<'w, 's> {
+    /// Create a new [`Derived`]. The derived's entity is reserved immediately, but it cannot
+    /// be read until the commands have been applied.
+    #[track_caller]
+    fn create_derived<R: 'static, F: Send + Sync + 'static + Fn(&mut Rcx) -> R>(
+        &mut self,
+        compute: F,
+    ) -> Derived<R> {
+        let derived = self.spawn(DerivedCell::new(compute)).id();
+        Derived {
+            id: derived,
+            origin: SignalOrigin::caller(),
+            marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::CreateMutable;
+
+    use super::*;
+
+    #[test]
+    fn test_cached_derived() {
+        let mut world = World::default();
+        let mutable = world.create_mutable::<i32>(1);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_inner = calls.clone();
+        let derived = create_cached_derived(&mut world, move |rcx| {
+            calls_inner.fetch_add(1, Ordering::Relaxed);
+            mutable.get(rcx) * 2
+        });
+        world.increment_change_tick();
+
+        // Repeated reads only compute once.
+        assert_eq!(world.read_derived(&derived), 2);
+        assert_eq!(world.read_derived(&derived), 2);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // Readers inherit the dependencies of the derived.
+        let owner = world.spawn_empty().id();
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        assert_eq!(rcx.read_derived(&derived), 2);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert!(!scope.dependencies_changed(&world, world.read_change_tick()));
+
+        // Changing a dependency invalidates the cache.
+        world.increment_change_tick();
+        mutable.set(&mut world, 2);
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+        assert_eq!(world.read_derived(&derived), 4);
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_cached_derived_cleanups() {
+        let mut app = App::new();
+        app.add_plugins(crate::SignalsPlugin);
+        let world = app.world_mut();
+        let mutable = world.create_mutable::<i32>(1);
+        let cleanups = Arc::new(AtomicUsize::new(0));
+        let cleanups_inner = cleanups.clone();
+        let derived = create_cached_derived(world, move |rcx| {
+            let cleanups = cleanups_inner.clone();
+            rcx.on_cleanup(move |_| {
+                cleanups.fetch_add(1, Ordering::Relaxed);
+            });
+            mutable.get(rcx)
+        });
+        world.increment_change_tick();
+        assert_eq!(world.read_derived(&derived), 1);
+        app.update();
+        assert_eq!(cleanups.load(Ordering::Relaxed), 0);
+
+        // Recomputing runs the cleanups of the previous computation on the next update.
+        let world = app.world_mut();
+        world.increment_change_tick();
+        mutable.set(world, 2);
+        assert_eq!(world.read_derived(&derived), 2);
+        app.update();
+        assert_eq!(cleanups.load(Ordering::Relaxed), 1);
+
+        // Despawning runs the cleanups of the current computation.
+        app.world_mut().despawn(derived.id());
+        assert_eq!(cleanups.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "was read while computing its own value")]
+    fn test_cached_derived_cycle() {
+        let mut world = World::default();
+        let this = Arc::new(std::sync::OnceLock::<Derived<i32>>::new());
+        let this_inner = this.clone();
+        let derived = create_cached_derived(&mut world, move |rcx| {
+            this_inner
+                .get()
+                .map_or(0, |this| rcx.read_derived(this) + 1)
+        });
+        let _ = this.set(derived);
+        world.read_derived(&derived);
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+use crate::{CallbackOwner, ReactionCell, TrackingScope};
+
+/// Marker component, required by every mutable cell, which allows mutables of any type to be
+/// counted.
+#[derive(Component, Default)]
+pub(crate) struct MutableMarker;
+
+/// Marker component, required by every derived cell, cached or not, which allows deriveds of
+/// any type to be counted.
+#[derive(Component, Default)]
+pub(crate) struct DerivedMarker;
+
+/// A resource which, if inserted, is updated at the end of each frame with the number of
+/// reactive objects which are alive. A count which keeps growing as UI is built and despawned
+/// indicates a leak.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReactiveObjectCounts {
+    /// The number of entities with a [`TrackingScope`].
+    pub tracking_scopes: usize,
+
+    /// The number of entities with a [`ReactionCell`].
+    pub reactions: usize,
+
+    /// The number of mutable cells, of any type.
+    pub mutables: usize,
+
+    /// The number of derived cells, cached or not, of any type.
+    pub deriveds: usize,
+
+    /// The number of callbacks owned by entities through a [`CallbackOwner`]. One-shot systems
+    /// which were registered by other code are not included.
+    pub callbacks: usize,
+}
+
+impl ReactiveObjectCounts {
+    /// Count the reactive objects which are currently alive.
+    pub fn count(world: &mut World) -> Self {
+        ObjectQueries::from_world(world).count(world)
+    }
+
+    /// The total number of reactive objects of all kinds.
+    pub fn total(&self) -> usize {
+        self.tracking_scopes + self.reactions + self.mutables + self.deriveds + self.callbacks
+    }
+}
+
+/// The queries used to count reactive objects, which are kept between frames so that they
+/// don't need to be rebuilt each time the counts are updated.
+pub(crate) struct ObjectQueries {
+    tracking_scopes: QueryState<(), With<TrackingScope>>,
+    reactions: QueryState<(), With<ReactionCell>>,
+    mutables: QueryState<(), With<MutableMarker>>,
+    deriveds: QueryState<(), With<DerivedMarker>>,
+    callbacks: QueryState<&'static CallbackOwner>,
+}
+
+impl FromWorld for ObjectQueries {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            tracking_scopes: world.query_filtered(),
+            reactions: world.query_filtered(),
+            mutables: world.query_filtered(),
+            deriveds: world.query_filtered(),
+            callbacks: world.query(),
+        }
+    }
+}
+
+impl ObjectQueries {
+    fn count(&mut self, world: &World) -> ReactiveObjectCounts {
+        ReactiveObjectCounts {
+            tracking_scopes: self.tracking_scopes.iter(world).count(),
+            reactions: self.reactions.iter(world).count(),
+            mutables: self.mutables.iter(world).count(),
+            deriveds: self.deriveds.iter(world).count(),
+            callbacks: self.callbacks.iter(world).map(CallbackOwner::len).sum(),
+        }
+    }
+}
+
+pub(crate) fn update_reactive_object_counts(world: &mut World, mut queries: Local<ObjectQueries>) {
+    let counts = queries.count(world);
+    *world.resource_mut::<ReactiveObjectCounts>() = counts;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_cached_derived, create_derived, create_mutable, Callback, SignalsPlugin};
+
+    #[test]
+    fn test_reactive_object_counts() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin)
+            .init_resource::<ReactiveObjectCounts>();
+        let world = app.world_mut();
+        let owner = world.spawn_empty().id();
+        let mutable = create_mutable(world, owner, 1);
+        create_mutable(world, owner, "text");
+        let derived = create_derived(world, move |rcx| mutable.get(rcx));
+        let cached = create_cached_derived(world, move |rcx| mutable.get(rcx));
+        // Only systems which are owned as callbacks are counted.
+        world.register_system(|| {});
+        let mut callbacks = CallbackOwner::new();
+        callbacks.add(Callback::new(world.register_system(|_: In<()>| {})));
+        world.entity_mut(owner).insert(callbacks);
+        app.update();
+        let counts = *app.world().resource::<ReactiveObjectCounts>();
+        assert_eq!(counts.mutables, 2);
+        assert_eq!(counts.deriveds, 2);
+        assert_eq!(counts.callbacks, 1);
+
+        let world = app.world_mut();
+        world.despawn(derived.id());
+        world.despawn(cached.id());
+        world.entity_mut(owner).despawn_recursive();
+        app.update();
+        let counts = *app.world().resource::<ReactiveObjectCounts>();
+        assert_eq!(counts.mutables, 0);
+        assert_eq!(counts.deriveds, 0);
+        assert_eq!(counts.callbacks, 0);
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use crate::ChangedDependency;
+
+/// Resource which controls how many times reactions may fail to converge in a single run
+/// before the run is abandoned. A divergence is a pass in which the number of reactions that
+/// ran did not decrease relative to the previous pass.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct ReactionDivergenceLimit(pub usize);
+
+impl Default for ReactionDivergenceLimit {
+    fn default() -> Self {
+        Self(32)
+    }
+}
+
+/// Event which is sent when reactions fail to converge. When this happens, the remaining
+/// reactions are not run for the current frame; they will be run again the next time that
+/// reactions are run.
+#[derive(Event, Debug, Clone)]
+pub struct ReactionDivergence {
+    /// The number of passes that were run before giving up.
+    pub iterations: usize,
+
+    /// The reactions which kept running while the run was failing to converge.
+    pub scopes: Vec<DivergentScope>,
+}
+
+/// A reaction which was involved in a failure to converge.
+#[derive(Debug, Clone)]
+pub struct DivergentScope {
+    /// The entity which owns the tracking scope.
+    pub entity: Entity,
+
+    /// The name of the entity, if it has one.
+    pub name: Option<Name>,
+
+    /// The dependencies which triggered the reaction while the run was failing to converge.
+    pub dependencies: Vec<ChangedDependency>,
+}
+
+/// Log and send a [`ReactionDivergence`] event describing the given reactions, along with
+/// the dependencies which triggered them.
+pub(crate) fn report_divergence(
+    world: &mut World,
+    suspects: Vec<(Entity, Vec<ChangedDependency>)>,
+    iterations: usize,
+) {
+    let scopes: Vec<DivergentScope> = suspects
+        .into_iter()
+        .map(|(entity, dependencies)| DivergentScope {
+            entity,
+            name: world.get::<Name>(entity).cloned(),
+            dependencies,
+        })
+        .collect();
+
+    let summary = scopes
+        .iter()
+        .map(|scope| {
+            let name = scope
+                .name
+                .as_ref()
+                .map_or_else(|| format!("{}", scope.entity), |name| format!("{}", name));
+            let deps = scope
+                .dependencies
+                .iter()
+                .map(|dep| match dep.entity {
+                    Some(entity) => format!("{}@{}", dep.type_name, entity),
+                    None => dep.type_name.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{} [{}]", name, deps)
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    warn!(
+        "Reactions failed to converge after {} iterations: {}",
+        iterations, summary
+    );
+
+    if world.contains_resource::<Events<ReactionDivergence>>() {
+        world.send_event(ReactionDivergence { iterations, scopes });
+    }
+}
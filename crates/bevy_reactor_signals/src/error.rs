@@ -0,0 +1,172 @@
+use std::{fmt, panic::Location};
+
+use bevy::prelude::Entity;
+
+/// Where a signal was created. This is only recorded in debug builds; in release builds it
+/// takes up no space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SignalOrigin {
+    #[cfg(debug_assertions)]
+    location: &'static Location<'static>,
+}
+
+impl SignalOrigin {
+    /// Record the location of the caller.
+    #[track_caller]
+    pub(crate) fn caller() -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            location: Location::caller(),
+        }
+    }
+}
+
+/// The reason that a signal could not be read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignalErrorKind {
+    /// The entity which holds the signal has been despawned.
+    Despawned,
+
+    /// The entity which holds the signal does not contain a value of the expected type.
+    WrongType,
+}
+
+/// Error returned by the fallible accessors of signals, such as [`Signal::try_get`](crate::Signal::try_get).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignalError {
+    kind: SignalErrorKind,
+    entity: Entity,
+    #[cfg(debug_assertions)]
+    name: &'static str,
+    #[cfg(debug_assertions)]
+    location: &'static Location<'static>,
+}
+
+impl SignalError {
+    /// Construct a new error for the signal of type `S` held by `entity`.
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    pub(crate) fn new<S: ?Sized>(
+        kind: SignalErrorKind,
+        entity: Entity,
+        origin: SignalOrigin,
+    ) -> Self {
+        Self {
+            kind,
+            entity,
+            #[cfg(debug_assertions)]
+            name: std::any::type_name::<S>(),
+            #[cfg(debug_assertions)]
+            location: origin.location,
+        }
+    }
+
+    /// The reason that the signal could not be read.
+    pub fn kind(&self) -> SignalErrorKind {
+        self.kind
+    }
+
+    /// The entity which holds the signal.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// The type name of the signal, such as `Mutable<i32>`. Only available in debug builds.
+    pub fn name(&self) -> Option<&'static str> {
+        #[cfg(debug_assertions)]
+        {
+            Some(self.name)
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            None
+        }
+    }
+
+    /// The location of the code which created the signal. Only available in debug builds.
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        #[cfg(debug_assertions)]
+        {
+            Some(self.location)
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            None
+        }
+    }
+}
+
+impl fmt::Display for SignalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.name(), self.location()) {
+            (Some(name), Some(location)) => {
+                write!(f, "{} {:?} created at {}", name, self.entity, location)?
+            }
+            _ => write!(f, "Signal {:?}", self.entity)?,
+        }
+        match self.kind {
+            SignalErrorKind::Despawned => write!(f, " has been despawned"),
+            SignalErrorKind::WrongType => write!(f, " does not hold a value of the expected type"),
+        }
+    }
+}
+
+impl std::error::Error for SignalError {}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::{
+        create_derived, create_mutable, create_mutable_with_history, Rcx, Signal, TrackingScope,
+    };
+
+    #[test]
+    fn test_signal_errors() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let mutable = create_mutable(&mut world, owner, 1);
+        #[cfg(debug_assertions)]
+        let line = line!() - 2;
+        let derived = create_derived(&mut world, move |rcx| mutable.get(rcx) + 1);
+        assert_eq!(mutable.try_get(&world), Ok(1));
+        assert_eq!(derived.try_get(&world), Ok(2));
+        assert_eq!(Signal::Derived(derived).try_map(&world, |v| v * 2), Ok(4));
+
+        world.despawn(mutable.id());
+        let err = mutable.try_get(&world).unwrap_err();
+        assert_eq!(err.kind(), SignalErrorKind::Despawned);
+        assert_eq!(err.entity(), mutable.id());
+        #[cfg(debug_assertions)]
+        {
+            assert!(err.name().unwrap().contains("Mutable<i32>"));
+            assert_eq!(err.location().unwrap().file(), file!());
+            assert_eq!(err.location().unwrap().line(), line);
+            assert!(err.to_string().ends_with("has been despawned"));
+        }
+
+        // Reading from a reactive context also returns an error rather than panicking.
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        assert_eq!(mutable.signal().try_get(&rcx), Err(err));
+        assert_eq!(Signal::Constant(3).try_get(&rcx), Ok(3));
+
+        world.despawn(derived.id());
+        let err = derived.try_get_clone(&world).unwrap_err();
+        assert_eq!(err.kind(), SignalErrorKind::Despawned);
+        assert_eq!(err.entity(), derived.id());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_wrapper_origin() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let history = create_mutable_with_history(&mut world, owner, 1, 10);
+        let line = line!() - 1;
+        world.despawn(history.mutable().id());
+        let err = history.mutable().try_get(&world).unwrap_err();
+        assert_eq!(err.location().unwrap().file(), file!());
+        assert_eq!(err.location().unwrap().line(), line);
+    }
+}
@@ -0,0 +1,192 @@
+use std::sync::Mutex;
+
+use bevy::{core::Name, ecs::event::EventCursor, prelude::*, utils::HashMap};
+
+use crate::{
+    create_mutable, Rcx, Reaction, ReactionCell, ReactionKind, ReactionSet, Signal, TrackingScope,
+    WriteMutable,
+};
+
+/// Resource which keeps track of the position of each reader of events of type `E`.
+#[derive(Resource)]
+pub(crate) struct EventSubscribers<E: Event> {
+    /// The position of each reader within the event queue, indexed by the entity which holds
+    /// the reader's tracking scope. This is behind a mutex because events are read from
+    /// reactive contexts which only have an immutable world.
+    readers: Mutex<HashMap<Entity, EventCursor<E>>>,
+
+    /// The position of the most recent event which readers have been notified of.
+    latest: EventCursor<E>,
+}
+
+impl<E: Event> Default for EventSubscribers<E> {
+    fn default() -> Self {
+        Self {
+            readers: Mutex::new(HashMap::default()),
+            latest: EventCursor::default(),
+        }
+    }
+}
+
+/// Return the events of type `E` which have arrived since the reaction whose tracking scope is
+/// `scope` last read them, and subscribe the reaction to the arrival of further events.
+pub(crate) fn read_events_for<E: Event + Clone>(
+    world: &World,
+    scope: &mut TrackingScope,
+) -> Vec<E> {
+    let Some(subscribers) = world.get_resource::<EventSubscribers<E>>() else {
+        panic!(
+            "Event {} must be registered with add_reactive_event before it can be read",
+            std::any::type_name::<E>()
+        );
+    };
+    let events = world.resource::<Events<E>>();
+    match scope.entity() {
+        Some(entity) => {
+            let mut readers = subscribers.readers.lock().unwrap();
+            let cursor = readers.entry(entity).or_insert_with(|| events.get_cursor());
+            cursor.read(events).cloned().collect()
+        }
+        None => {
+            // The scope hasn't been inserted yet, so hand the cursor over once it has.
+            let mut cursor = events.get_cursor();
+            let result = cursor.read(events).cloned().collect();
+            scope.attach(move |world, entity| {
+                if let Some(subscribers) = world.get_resource::<EventSubscribers<E>>() {
+                    let mut readers = subscribers.readers.lock().unwrap();
+                    readers.entry(entity).or_insert(cursor);
+                }
+            });
+            result
+        }
+    }
+}
+
+/// System which marks the tracking scope of each reader as changed when new events arrive,
+/// and forgets the positions of readers which have been despawned.
+fn notify_event_readers<E: Event>(world: &mut World) {
+    world.resource_scope(|world, mut subscribers: Mut<EventSubscribers<E>>| {
+        let subscribers = subscribers.bypass_change_detection();
+        let events = world.resource::<Events<E>>();
+        let arrived = !subscribers.latest.is_empty(events);
+        subscribers.latest.clear(events);
+        let readers = subscribers.readers.get_mut().unwrap();
+        readers.retain(|entity, cursor| {
+            let Some(scope) = world.get::<TrackingScope>(*entity) else {
+                return false;
+            };
+            if arrived && !cursor.is_empty(events) {
+                scope.set_changed();
+            }
+            true
+        });
+    });
+}
+
+/// Extension trait which allows events to be read from reactive contexts.
+pub trait AddReactiveEvent {
+    /// Register an event type which can be read by reactions, using
+    /// [`Rcx::read_events`](crate::Rcx::read_events) or [`create_event_signal`]. This also
+    /// adds the event to the app, if it hasn't been already.
+    fn add_reactive_event<E: Event>(&mut self) -> &mut Self;
+}
+
+impl AddReactiveEvent for App {
+    fn add_reactive_event<E: Event>(&mut self) -> &mut Self {
+        if self.world().contains_resource::<EventSubscribers<E>>() {
+            return self;
+        }
+        self.add_event::<E>()
+            .init_resource::<EventSubscribers<E>>()
+            .add_systems(Update, notify_event_readers::<E>.before(ReactionSet))
+    }
+}
+
+/// Reaction which folds new events into the value of a mutable.
+struct EventSignalReaction<E, T, F: Fn(&mut T, &E)> {
+    reducer: F,
+    marker: std::marker::PhantomData<fn(E, T)>,
+}
+
+impl<E: Event + Clone, T: Send + Sync + 'static, F: Send + Sync + Fn(&mut T, &E)> Reaction
+    for EventSignalReaction<E, T, F>
+{
+    fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+        let events = Rcx::new(world, owner, tracking).read_events::<E>();
+        if !events.is_empty() {
+            world.update_mutable(owner, |mut value: Mut<T>| {
+                for event in events.iter() {
+                    (self.reducer)(&mut value, event);
+                }
+            });
+        }
+    }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Memo
+    }
+}
+
+/// Create a signal, owned by `parent`, whose value is computed by folding each new event of
+/// type `E` into the previous value using `reducer`. The event type must have been registered
+/// with [`AddReactiveEvent::add_reactive_event`].
+#[track_caller]
+pub fn create_event_signal<
+    E: Event + Clone,
+    T: PartialEq + Send + Sync + 'static,
+    F: Send + Sync + 'static + Fn(&mut T, &E),
+>(
+    world: &mut World,
+    parent: Entity,
+    init: T,
+    reducer: F,
+) -> Signal<T> {
+    let mut scope = TrackingScope::new(world.change_tick());
+    let mutable = create_mutable(world, parent, init);
+    let mut reaction = EventSignalReaction {
+        reducer,
+        marker: std::marker::PhantomData,
+    };
+    reaction.react(mutable.id(), world, &mut scope);
+    world.entity_mut(mutable.id()).insert((
+        ReactionCell::new(reaction),
+        scope,
+        Name::new(format!("EventSignal::<{}>", std::any::type_name::<E>())),
+    ));
+    mutable.signal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SignalsPlugin;
+
+    #[derive(Event, Clone)]
+    struct LogMessage(String);
+
+    #[test]
+    fn test_event_signal() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin)
+            .add_reactive_event::<LogMessage>();
+        let world = app.world_mut();
+        world.send_event(LogMessage("early".into()));
+        let owner = world.spawn_empty().id();
+        let log = create_event_signal(world, owner, Vec::new(), |log, msg: &LogMessage| {
+            log.push(msg.0.clone());
+        });
+        assert_eq!(log.get_clone(app.world()), vec!["early".to_string()]);
+
+        // Nothing changes when there are no new events.
+        app.update();
+        assert_eq!(log.get_clone(app.world()).len(), 1);
+
+        app.world_mut().send_event(LogMessage("a".into()));
+        app.world_mut().send_event(LogMessage("b".into()));
+        app.update();
+        assert_eq!(log.get_clone(app.world()), vec!["early", "a", "b"]);
+        app.update();
+        app.update();
+        assert_eq!(log.get_clone(app.world()).len(), 3);
+    }
+}
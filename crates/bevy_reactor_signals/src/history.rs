@@ -0,0 +1,433 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    create_derived, create_mutable, Derived, Mutable, ReadMutable, SetOwner, Signal, WriteMutable,
+};
+
+/// A single entry in the undo or redo stack.
+struct HistoryEntry<T> {
+    /// The name of the transaction or coalesced edit which produced the entry, if any.
+    label: Option<String>,
+
+    /// The value to restore.
+    value: T,
+}
+
+/// State of an open transaction.
+struct Transaction<T> {
+    /// The name of the transaction.
+    label: String,
+
+    /// The value of the mutable when the transaction began.
+    value: T,
+
+    /// Number of nested calls to `begin_transaction`.
+    depth: usize,
+}
+
+/// The undo and redo stacks for a [`MutableWithHistory`].
+pub(crate) struct History<T> {
+    undo: VecDeque<HistoryEntry<T>>,
+    redo: Vec<HistoryEntry<T>>,
+
+    /// Maximum number of entries in the undo stack.
+    capacity: usize,
+
+    /// The open transaction, if any.
+    transaction: Option<Transaction<T>>,
+
+    /// The key of the most recent coalesced edit, if no other edit has happened since.
+    coalescing: Option<String>,
+}
+
+impl<T> History<T> {
+    /// Push a new undo entry, discarding the redo stack and the oldest entries if the
+    /// capacity is exceeded.
+    fn push(&mut self, label: Option<String>, value: T) {
+        self.redo.clear();
+        self.undo.push_back(HistoryEntry { label, value });
+        while self.undo.len() > self.capacity {
+            self.undo.pop_front();
+        }
+    }
+}
+
+/// A [`Mutable`] which records its prior values, so that edits can be undone and redone.
+///
+/// Only writes made through the methods of this type are recorded; writes made directly to
+/// the underlying mutable bypass the history.
+pub struct MutableWithHistory<T> {
+    value: Mutable<T>,
+    history: Mutable<History<T>>,
+    can_undo: Derived<bool>,
+    can_redo: Derived<bool>,
+}
+
+impl<T> Copy for MutableWithHistory<T> {}
+impl<T> Clone for MutableWithHistory<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> MutableWithHistory<T>
+where
+    T: PartialEq + Clone + Send + Sync + 'static,
+{
+    /// The underlying [`Mutable`]. Writes made directly to the mutable are not recorded.
+    pub fn mutable(&self) -> Mutable<T> {
+        self.value
+    }
+
+    /// Returns a signal for the current value.
+    pub fn signal(&self) -> Signal<T> {
+        self.value.signal()
+    }
+
+    /// Get the current value with Clone semantics.
+    ///
+    /// Arguments:
+    /// * `cx`: The reactive context.
+    pub fn get_clone<R: ReadMutable>(&self, cx: &mut R) -> T {
+        cx.read_mutable_clone(&self.value)
+    }
+
+    /// Returns a signal which is true when there is an edit which can be undone.
+    pub fn can_undo(&self) -> Signal<bool> {
+        Signal::Derived(self.can_undo)
+    }
+
+    /// Returns a signal which is true when there is an edit which can be redone.
+    pub fn can_redo(&self) -> Signal<bool> {
+        Signal::Derived(self.can_redo)
+    }
+
+    /// The label of the edit which would be undone, if it has one.
+    pub fn undo_label<R: ReadMutable>(&self, cx: &R) -> Option<String> {
+        cx.read_mutable_map(&self.history, |h| {
+            h.undo.back().and_then(|entry| entry.label.clone())
+        })
+    }
+
+    /// The label of the edit which would be redone, if it has one.
+    pub fn redo_label<R: ReadMutable>(&self, cx: &R) -> Option<String> {
+        cx.read_mutable_map(&self.history, |h| {
+            h.redo.last().and_then(|entry| entry.label.clone())
+        })
+    }
+
+    /// Set the value, recording the prior value as a new history entry. Does nothing if the
+    /// value being set matches the existing value.
+    ///
+    /// Arguments:
+    /// * `cx`: The reactive context.
+    /// * `value`: The new value.
+    pub fn set<W: WriteMutable>(&self, cx: &mut W, value: T) {
+        if let Some(prior) = self.replace(cx, value) {
+            self.record(cx, None, prior);
+        }
+    }
+
+    /// Set the value, merging the edit with the previous one if it was also made with
+    /// `set_coalesced` using the same key. This is used for continuous edits, such as dragging
+    /// a slider, so that the whole edit can be undone in a single step. Coalescing stops when
+    /// any other edit is made, or when [`seal`](Self::seal) is called.
+    ///
+    /// Arguments:
+    /// * `cx`: The reactive context.
+    /// * `key`: Identifies the continuous edit; also used as the label of the entry.
+    /// * `value`: The new value.
+    pub fn set_coalesced<W: WriteMutable>(&self, cx: &mut W, key: &str, value: T) {
+        let Some(prior) = self.replace(cx, value) else {
+            return;
+        };
+        let key = key.to_string();
+        cx.update_mutable(self.history.id(), |mut h: Mut<History<T>>| {
+            if h.transaction.is_some() {
+                return;
+            }
+            if h.coalescing.as_ref() != Some(&key) || h.undo.is_empty() {
+                h.push(Some(key.clone()), prior);
+                h.coalescing = Some(key);
+            }
+        });
+    }
+
+    /// Update the value in place using a callback, recording the prior value as a new history
+    /// entry if the value was changed.
+    pub fn update<W: WriteMutable, F: FnOnce(Mut<T>)>(&self, cx: &mut W, updater: F) {
+        let mut prior = None;
+        cx.update_mutable(self.value.id(), |mut value: Mut<T>| {
+            let before = (*value).clone();
+            updater(value.reborrow());
+            if *value != before {
+                prior = Some(before);
+            }
+        });
+        if let Some(prior) = prior {
+            self.record(cx, None, prior);
+        }
+    }
+
+    /// Stop coalescing edits, so that the next call to
+    /// [`set_coalesced`](Self::set_coalesced) creates a new entry.
+    pub fn seal<W: WriteMutable>(&self, cx: &mut W) {
+        cx.update_mutable(self.history.id(), |mut h: Mut<History<T>>| {
+            if h.coalescing.is_some() {
+                h.coalescing = None;
+            }
+        });
+    }
+
+    /// Begin a named transaction. All of the edits made until the matching call to
+    /// [`commit_transaction`](Self::commit_transaction) are grouped into a single entry.
+    /// Transactions can be nested, in which case the outermost transaction's name is used.
+    pub fn begin_transaction<W: WriteMutable>(&self, cx: &mut W, name: impl Into<String>) {
+        let mut current = None;
+        cx.update_mutable(self.value.id(), |value: Mut<T>| {
+            current = Some((*value).clone());
+        });
+        let name = name.into();
+        cx.update_mutable(self.history.id(), |mut h: Mut<History<T>>| {
+            h.coalescing = None;
+            match h.transaction.as_mut() {
+                Some(transaction) => transaction.depth += 1,
+                None => {
+                    h.transaction = Some(Transaction {
+                        label: name,
+                        value: current.unwrap(),
+                        depth: 1,
+                    })
+                }
+            }
+        });
+    }
+
+    /// Commit the current transaction. If the value changed during the transaction, a single
+    /// entry is recorded which restores the value from before the transaction began.
+    pub fn commit_transaction<W: WriteMutable>(&self, cx: &mut W) {
+        let mut current = None;
+        cx.update_mutable(self.value.id(), |value: Mut<T>| {
+            current = Some((*value).clone());
+        });
+        let current = current.unwrap();
+        cx.update_mutable(self.history.id(), |mut h: Mut<History<T>>| {
+            let Some(transaction) = h.transaction.as_mut() else {
+                warn!("commit_transaction called without a matching begin_transaction");
+                return;
+            };
+            transaction.depth -= 1;
+            if transaction.depth > 0 {
+                return;
+            }
+            let transaction = h.transaction.take().unwrap();
+            if transaction.value != current {
+                h.push(Some(transaction.label), transaction.value);
+            }
+        });
+    }
+
+    /// Undo the most recent edit. Does nothing if there is no edit to undo, or if a
+    /// transaction is open.
+    pub fn undo<W: WriteMutable>(&self, cx: &mut W) {
+        let mut entry = None;
+        cx.update_mutable(self.history.id(), |mut h: Mut<History<T>>| {
+            if h.transaction.is_none() && !h.undo.is_empty() {
+                h.coalescing = None;
+                entry = h.undo.pop_back();
+            }
+        });
+        let Some(entry) = entry else {
+            return;
+        };
+        let current = self.restore(cx, entry.value);
+        cx.update_mutable(self.history.id(), |mut h: Mut<History<T>>| {
+            h.redo.push(HistoryEntry {
+                label: entry.label,
+                value: current,
+            });
+        });
+    }
+
+    /// Redo the most recently undone edit. Does nothing if there is no edit to redo, or if a
+    /// transaction is open.
+    pub fn redo<W: WriteMutable>(&self, cx: &mut W) {
+        let mut entry = None;
+        cx.update_mutable(self.history.id(), |mut h: Mut<History<T>>| {
+            if h.transaction.is_none() && !h.redo.is_empty() {
+                h.coalescing = None;
+                entry = h.redo.pop();
+            }
+        });
+        let Some(entry) = entry else {
+            return;
+        };
+        let current = self.restore(cx, entry.value);
+        cx.update_mutable(self.history.id(), |mut h: Mut<History<T>>| {
+            h.undo.push_back(HistoryEntry {
+                label: entry.label,
+                value: current,
+            });
+        });
+    }
+
+    /// Replace the value, returning the prior value if it was different.
+    fn replace<W: WriteMutable>(&self, cx: &mut W, value: T) -> Option<T> {
+        let mut prior = None;
+        cx.update_mutable(self.value.id(), |mut current: Mut<T>| {
+            if *current != value {
+                prior = Some(std::mem::replace(&mut *current, value));
+            }
+        });
+        prior
+    }
+
+    /// Replace the value unconditionally, returning the prior value.
+    fn restore<W: WriteMutable>(&self, cx: &mut W, value: T) -> T {
+        let mut prior = None;
+        cx.update_mutable(self.value.id(), |mut current: Mut<T>| {
+            prior = Some(std::mem::replace(&mut *current, value));
+        });
+        prior.unwrap()
+    }
+
+    /// Record a prior value, unless a transaction is open.
+    fn record<W: WriteMutable>(&self, cx: &mut W, label: Option<String>, prior: T) {
+        cx.update_mutable(self.history.id(), |mut h: Mut<History<T>>| {
+            if h.transaction.is_none() {
+                h.coalescing = None;
+                h.push(label, prior);
+            }
+        });
+    }
+}
+
+impl<T> MutableWithHistory<T>
+where
+    T: PartialEq + Copy + Send + Sync + 'static,
+{
+    /// Get the current value with Copy semantics.
+    ///
+    /// Arguments:
+    /// * `cx`: The reactive context.
+    pub fn get<R: ReadMutable>(&self, cx: &R) -> T {
+        cx.read_mutable(&self.value)
+    }
+}
+
+/// Function to create a mutable with undo/redo history. At most `capacity` edits are retained;
+/// older edits are discarded.
+#[track_caller]
+pub fn create_mutable_with_history<T>(
+    world: &mut World,
+    parent: Entity,
+    init: T,
+    capacity: usize,
+) -> MutableWithHistory<T>
+where
+    T: PartialEq + Clone + Send + Sync + 'static,
+{
+    let value = create_mutable(world, parent, init);
+    let history = create_mutable(
+        world,
+        parent,
+        History::<T> {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            capacity,
+            transaction: None,
+            coalescing: None,
+        },
+    );
+    let can_undo = create_derived(world, move |rcx| {
+        rcx.read_mutable_map(&history, |h| !h.undo.is_empty())
+    });
+    let can_redo = create_derived(world, move |rcx| {
+        rcx.read_mutable_map(&history, |h| !h.redo.is_empty())
+    });
+    world.entity_mut(can_undo.id()).set_owner(parent);
+    world.entity_mut(can_redo.id()).set_owner(parent);
+    MutableWithHistory {
+        value,
+        history,
+        can_undo,
+        can_redo,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let value = create_mutable_with_history(&mut world, owner, 0, 3);
+        assert!(!value.can_undo().get(&world));
+        assert!(!value.can_redo().get(&world));
+
+        value.set(&mut world, 1);
+        value.set(&mut world, 1);
+        value.update(&mut world, |mut v| *v += 1);
+        assert_eq!(value.get(&world), 2);
+        assert!(value.can_undo().get(&world));
+
+        value.undo(&mut world);
+        assert_eq!(value.get(&world), 1);
+        assert!(value.can_redo().get(&world));
+        value.undo(&mut world);
+        assert_eq!(value.get(&world), 0);
+        assert!(!value.can_undo().get(&world));
+        value.undo(&mut world);
+        assert_eq!(value.get(&world), 0);
+
+        value.redo(&mut world);
+        assert_eq!(value.get(&world), 1);
+
+        // A new edit discards the redo stack.
+        value.set(&mut world, 5);
+        assert!(!value.can_redo().get(&world));
+
+        // Old entries are discarded when the capacity is exceeded.
+        value.set(&mut world, 6);
+        value.set(&mut world, 7);
+        for _ in 0..5 {
+            value.undo(&mut world);
+        }
+        assert_eq!(value.get(&world), 1);
+    }
+
+    #[test]
+    fn test_transactions_and_coalescing() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let value = create_mutable_with_history(&mut world, owner, 0, 10);
+
+        value.begin_transaction(&mut world, "Reset");
+        value.set(&mut world, 1);
+        value.begin_transaction(&mut world, "Inner");
+        value.set(&mut world, 2);
+        value.commit_transaction(&mut world);
+        value.undo(&mut world);
+        assert_eq!(value.get(&world), 2);
+        value.commit_transaction(&mut world);
+        assert_eq!(value.undo_label(&world).as_deref(), Some("Reset"));
+
+        // Continuous edits with the same key are merged until sealed.
+        value.set_coalesced(&mut world, "Drag", 3);
+        value.set_coalesced(&mut world, "Drag", 4);
+        value.seal(&mut world);
+        value.set_coalesced(&mut world, "Drag", 5);
+        value.undo(&mut world);
+        assert_eq!(value.get(&world), 4);
+        assert_eq!(value.redo_label(&world).as_deref(), Some("Drag"));
+        value.undo(&mut world);
+        assert_eq!(value.get(&world), 2);
+        value.undo(&mut world);
+        assert_eq!(value.get(&world), 0);
+        assert!(!value.can_undo().get(&world));
+    }
+}
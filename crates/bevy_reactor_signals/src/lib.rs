@@ -1,37 +1,144 @@
 //! Implementation of the reactive signals pattern for Bevy.
 #![warn(missing_docs)]
 
-use bevy::app::{App, Plugin, Update};
+// Allows `#[derive(Store)]` to be used within this crate.
+extern crate self as bevy_reactor_signals;
 
+use bevy::{
+    app::{App, Last, Plugin, Update},
+    ecs::{
+        schedule::{
+            common_conditions::resource_exists, InternedScheduleLabel, IntoSystemConfigs,
+            ScheduleLabel, SystemSet,
+        },
+        system::Resource,
+        world::World,
+    },
+    utils::HashSet,
+};
+
+mod assets;
+mod async_resource;
+mod batch;
 mod callback;
+mod collections;
+mod dependency_index;
 mod derived;
+mod diagnostics;
+mod divergence;
 mod ecx;
+mod error;
+mod events;
+mod history;
 mod mutable;
+mod owner;
+mod peek;
+mod persist;
+mod profiler;
+mod query;
 mod rcx;
 mod reaction;
 mod signal;
+mod store;
+mod time;
 mod tracking_scope;
 
+pub use assets::{create_asset_state_signal, AddReactiveAsset};
+pub use async_resource::{create_async_resource, AsyncState, AsyncStatus};
+pub use batch::BatchMutations;
+use batch::MutableBatch;
 use callback::cleanup_callbacks;
-pub use callback::{Callback, CallbackOwner, RunCallback};
-pub use derived::{create_derived, Derived, ReadDerived};
+pub use callback::{Callback, CallbackOwner, RunCallback, RunCallbackThen};
+pub use collections::{
+    create_mutable_map, create_mutable_vec, CollectionChanges, MapOp, MutableMap, MutableVec, VecOp,
+};
+use dependency_index::DependencyIndex;
+pub use derived::{create_cached_derived, create_derived, CreateDerived, Derived, ReadDerived};
+use diagnostics::update_reactive_object_counts;
+pub use diagnostics::ReactiveObjectCounts;
+pub use divergence::{DivergentScope, ReactionDivergence, ReactionDivergenceLimit};
 pub use ecx::Ecx;
+pub use error::{SignalError, SignalErrorKind};
+pub use events::{create_event_signal, AddReactiveEvent};
+pub use history::{create_mutable_with_history, MutableWithHistory};
 pub use mutable::{create_mutable, CreateMutable, Mutable, ReadMutable, WriteMutable};
+use owner::register_owner_hooks;
+pub use owner::{DespawnOwned, OwnedBy, OwnedEntities, SetOwner};
+pub use peek::PeekWorld;
+use persist::save_persistent_mutables;
+pub use persist::{
+    create_persistent_mutable, FileStore, MemoryStore, PersistentStorage, PersistentStore,
+};
+pub use profiler::{ReactionProfiler, ReactionRun, ReactionStats};
+pub use query::{create_query, ReactiveQuery};
 pub use rcx::Rcx;
 pub use reaction::*;
 pub use signal::IntoSignal;
 pub use signal::Signal;
+#[doc(hidden)]
+pub use store::__private;
+pub use store::{create_store, Store, StoreHandle};
+pub use time::{create_debounced, create_interval, create_throttled, create_timeout};
 pub use tracking_scope::TrackingScope;
 pub use tracking_scope::TrackingScopeTracing;
-use tracking_scope::{cleanup_tracking_scopes, run_reactions};
+use tracking_scope::{
+    register_tracking_scope_hooks, run_reactions, run_reactions_in_schedule, RetiredCleanups,
+};
+pub use tracking_scope::{ChangedDependency, PolledDependency};
 
 /// Plugin that adds the reactive UI system to the app.
 pub struct SignalsPlugin;
 
 impl Plugin for SignalsPlugin {
     fn build(&self, app: &mut App) {
-        cleanup_tracking_scopes(app.world_mut());
+        register_tracking_scope_hooks(app.world_mut());
         cleanup_callbacks(app.world_mut());
-        app.add_systems(Update, run_reactions);
+        register_owner_hooks(app.world_mut());
+        app.init_resource::<DependencyIndex>()
+            .init_resource::<ReactionDivergenceLimit>()
+            .init_resource::<MutableBatch>()
+            .init_resource::<RetiredCleanups>()
+            .add_event::<ReactionDivergence>()
+            .add_systems(Update, run_reactions.in_set(ReactionSet))
+            .add_systems(Last, save_persistent_mutables)
+            .add_systems(
+                Last,
+                update_reactive_object_counts.run_if(resource_exists::<ReactiveObjectCounts>),
+            );
+    }
+}
+
+/// System set containing the systems which run reactions. This can be used to order other
+/// systems relative to reactions.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReactionSet;
+
+/// Extension trait which allows reactions to be run in additional schedules.
+pub trait AddReactionSchedule {
+    /// Add a pass which runs reactions in the given schedule, in addition to the default pass
+    /// in `Update`. Only tracking scopes which have opted in to the schedule, via
+    /// [`TrackingScope::set_schedule`], are run by this pass. The system is added to
+    /// [`ReactionSet`], which can be configured to order it within the schedule.
+    fn add_reaction_schedule(&mut self, schedule: impl ScheduleLabel) -> &mut Self;
+}
+
+/// The schedules which have had a reaction pass added by [`AddReactionSchedule`].
+#[derive(Resource, Default)]
+struct ReactionSchedules(HashSet<InternedScheduleLabel>);
+
+impl AddReactionSchedule for App {
+    fn add_reaction_schedule(&mut self, schedule: impl ScheduleLabel) -> &mut Self {
+        let label = schedule.intern();
+        let mut schedules = self
+            .world_mut()
+            .get_resource_or_insert_with(ReactionSchedules::default);
+        if !schedules.0.insert(label) {
+            return self;
+        }
+        self.add_systems(
+            schedule,
+            (move |world: &mut World| run_reactions_in_schedule(world, Some(label)))
+                .in_set(ReactionSet),
+        )
     }
 }
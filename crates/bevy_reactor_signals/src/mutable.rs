@@ -1,14 +1,22 @@
 use std::marker::PhantomData;
 
-use crate::signal::Signal;
+use crate::{
+    batch::{is_batching, record_batch_write},
+    dependency_index::{forget_write_tracking, record_write},
+    diagnostics::MutableMarker,
+    error::{SignalError, SignalErrorKind, SignalOrigin},
+    signal::Signal,
+    PeekWorld, SetOwner,
+};
 use bevy::{
     ecs::{component::ComponentId, world::DeferredWorld},
     prelude::*,
-    ui::experimental::GhostNode,
 };
 
 /// Contains a mutable reactive value.
 #[derive(Component)]
+#[require(MutableMarker)]
+#[component(on_remove = forget_write_tracking)]
 pub(crate) struct MutableCell<T>(pub(crate) T);
 
 /// Contains a reference to a reactive mutable variable.
@@ -16,8 +24,13 @@ pub(crate) struct MutableCell<T>(pub(crate) T);
 pub struct Mutable<T> {
     /// The entity that holds the mutable value.
     pub(crate) cell: Entity,
-    /// The component id for the mutable cell.
-    pub(crate) component: ComponentId,
+
+    /// The component id of the cell, if it was known when the mutable was created. Mutables
+    /// created via [`Commands`] don't know it, since the cell hasn't been spawned yet.
+    pub(crate) component: Option<ComponentId>,
+
+    /// Where the mutable was created, for error reporting.
+    pub(crate) origin: SignalOrigin,
 
     /// Marker
     pub(crate) marker: std::marker::PhantomData<T>,
@@ -28,6 +41,11 @@ impl<T> Mutable<T> {
     pub fn id(&self) -> Entity {
         self.cell
     }
+
+    /// Construct an error describing why this mutable could not be read.
+    pub(crate) fn error(&self, kind: SignalErrorKind) -> SignalError {
+        SignalError::new::<Self>(kind, self.cell, self.origin)
+    }
 }
 
 impl<T> Copy for Mutable<T> {}
@@ -41,11 +59,28 @@ impl<T> Mutable<T>
 where
     T: Send + Sync + 'static,
 {
+    /// The component id of the cell which holds the mutable value. This is `None` if no cell
+    /// of type `T` has been spawned yet, in which case the mutable can't be read either.
+    pub(crate) fn component_id(&self, world: &World) -> Option<ComponentId> {
+        self.component
+            .or_else(|| world.component_id::<MutableCell<T>>())
+    }
+
     /// Update a mutable value in place using a callback. The callback is passed a
     /// `Mut<T>` which can be used to modify the value.
     pub fn update<W: WriteMutable, F: FnOnce(Mut<T>)>(&self, w: &mut W, updater: F) {
         w.update_mutable(self.id(), updater);
     }
+
+    /// Read the value of this [`Mutable`] using a mapping function, returning an error if the
+    /// mutable has been despawned.
+    pub fn try_map<R: ReadMutable, U, F: FnOnce(&T) -> U>(
+        &self,
+        cx: &R,
+        f: F,
+    ) -> Result<U, SignalError> {
+        cx.try_read_mutable_map(self, f)
+    }
 }
 
 impl<T> Mutable<T>
@@ -78,6 +113,21 @@ where
         cx.read_mutable(self)
     }
 
+    /// Get the value of this [`Mutable`] with Copy semantics, returning an error if the
+    /// mutable has been despawned.
+    pub fn try_get<R: ReadMutable>(&self, cx: &R) -> Result<T, SignalError> {
+        cx.try_read_mutable_map(self, |value| *value)
+    }
+
+    /// Get the value of this [`Mutable`] with Copy semantics, without adding it as a
+    /// dependency of the current tracking scope.
+    ///
+    /// Arguments:
+    /// * `cx`: The reactive context.
+    pub fn peek<R: PeekWorld>(&self, cx: &R) -> T {
+        cx.peek_world().read_mutable(self)
+    }
+
     /// Set the value of this [`Mutable`] with Copy semantics.
     ///
     /// Arguments:
@@ -100,6 +150,21 @@ where
         cx.read_mutable_clone(self)
     }
 
+    /// Get the value of this [`Mutable`] with Clone semantics, returning an error if the
+    /// mutable has been despawned.
+    pub fn try_get_clone<R: ReadMutable>(&self, cx: &R) -> Result<T, SignalError> {
+        cx.try_read_mutable_map(self, T::clone)
+    }
+
+    /// Get the value of this [`Mutable`] with Clone semantics, without adding it as a
+    /// dependency of the current tracking scope.
+    ///
+    /// Arguments:
+    /// * `cx`: The reactive context.
+    pub fn peek_clone<R: PeekWorld>(&self, cx: &R) -> T {
+        cx.peek_world().read_mutable_clone(self)
+    }
+
     /// Set the value of this [`Mutable`] with Clone semantics.
     ///
     /// Arguments:
@@ -111,19 +176,17 @@ where
 }
 
 /// Function to create a mutable
+#[track_caller]
 pub fn create_mutable<T: Send + Sync + 'static>(
     world: &mut World,
     parent: Entity,
     init: T,
 ) -> Mutable<T> {
-    let cell = world
-        .spawn((MutableCell::<T>(init), GhostNode::default()))
-        .set_parent(parent)
-        .id();
-    let component = world.register_component::<MutableCell<T>>();
+    let cell = world.spawn(MutableCell::<T>(init)).set_owner(parent).id();
     Mutable {
         cell,
-        component,
+        component: world.component_id::<MutableCell<T>>(),
+        origin: SignalOrigin::caller(),
         marker: PhantomData,
     }
 }
@@ -151,6 +214,17 @@ pub trait ReadMutable {
     fn read_mutable_map<T, U, F: Fn(&T) -> U>(&self, mutable: &Mutable<T>, f: F) -> U
     where
         T: Send + Sync + 'static;
+
+    /// Read the value of a mutable variable using a mapping function, returning an error
+    /// if the mutable's cell has been despawned. Calling this function adds the mutable to the
+    /// current tracking scope if it could be read.
+    fn try_read_mutable_map<T, U, F: FnOnce(&T) -> U>(
+        &self,
+        mutable: &Mutable<T>,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        T: Send + Sync + 'static;
 }
 
 /// Trait for low-level write-access to mutables given an entity id.
@@ -174,6 +248,14 @@ pub trait CreateMutable {
     fn create_mutable<T>(&mut self, init: T) -> Mutable<T>
     where
         T: Send + Sync + 'static;
+
+    /// Create a new [`Mutable`] whose initial value is returned by `init`. The function is
+    /// passed the mutable's entity, so that it can access the world or add other components,
+    /// such as a reaction which keeps the value up to date.
+    fn create_mutable_with<T, F>(&mut self, init: F) -> Mutable<T>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(&mut EntityWorldMut) -> T + Send + 'static;
 }
 
 // /// Custom command which updates the state of a mutable cell.
@@ -198,32 +280,39 @@ impl ReadMutable for World {
     where
         T: Send + Sync + Copy + 'static,
     {
-        let mutable_entity = self.entity(mutable.cell);
-        mutable_entity.get::<MutableCell<T>>().unwrap().0
+        *expect_mutable_value(self, mutable)
     }
 
     fn read_mutable_clone<T>(&self, mutable: &Mutable<T>) -> T
     where
         T: Send + Sync + Clone + 'static,
     {
-        let mutable_entity = self.entity(mutable.cell);
-        mutable_entity.get::<MutableCell<T>>().unwrap().0.clone()
+        expect_mutable_value(self, mutable).clone()
     }
 
     fn read_mutable_as_ref<T>(&self, mutable: &Mutable<T>) -> &T
     where
         T: Send + Sync + 'static,
     {
-        let mutable_entity = self.entity(mutable.cell);
-        &mutable_entity.get::<MutableCell<T>>().unwrap().0
+        expect_mutable_value(self, mutable)
     }
 
     fn read_mutable_map<T, U, F: Fn(&T) -> U>(&self, mutable: &Mutable<T>, f: F) -> U
     where
         T: Send + Sync + 'static,
     {
-        let mutable_entity = self.entity(mutable.cell);
-        f(&mutable_entity.get::<MutableCell<T>>().unwrap().0)
+        f(expect_mutable_value(self, mutable))
+    }
+
+    fn try_read_mutable_map<T, U, F: FnOnce(&T) -> U>(
+        &self,
+        mutable: &Mutable<T>,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        T: Send + Sync + 'static,
+    {
+        mutable_value(self, mutable).map(f)
     }
 }
 
@@ -234,33 +323,86 @@ impl WriteMutable for World {
     where
         T: Send + Sync + PartialEq + 'static,
     {
-        let mut entt = self.entity_mut(mutable);
-        let mut cell = entt.get_mut::<MutableCell<T>>().unwrap();
-        if cell.0 != value {
-            cell.0 = value;
-        }
+        write_mutable_cell(&mut DeferredWorld::from(self), mutable, value);
     }
 
     fn update_mutable<T, F: FnOnce(Mut<T>)>(&mut self, mutable: Entity, updater: F)
     where
         T: Send + Sync + 'static,
     {
-        let value = self.get_mut::<MutableCell<T>>(mutable).unwrap();
-        let inner = value.map_unchanged(|v| &mut v.0);
-        (updater)(inner);
+        update_mutable_cell(&mut DeferredWorld::from(self), mutable, updater);
     }
 }
 
 impl CreateMutable for World {
+    #[track_caller]
     fn create_mutable<T>(&mut self, init: T) -> Mutable<T>
     where
         T: Send + Sync + 'static,
     {
         let cell = self.spawn(MutableCell::<T>(init)).id();
-        let component = self.register_component::<MutableCell<T>>();
         Mutable {
             cell,
-            component,
+            component: self.component_id::<MutableCell<T>>(),
+            origin: SignalOrigin::caller(),
+            marker: PhantomData,
+        }
+    }
+
+    #[track_caller]
+    fn create_mutable_with<T, F>(&mut self, init: F) -> Mutable<T>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(&mut EntityWorldMut) -> T + Send + 'static,
+    {
+        let mut entity = self.spawn_empty();
+        let value = init(&mut entity);
+        let cell = entity.insert(MutableCell::<T>(value)).id();
+        Mutable {
+            cell,
+            component: self.component_id::<MutableCell<T>>(),
+            origin: SignalOrigin::caller(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'w, 's> CreateMutable for Commands<'w, 's> {
+    /// Create a new [`Mutable`]. The mutable's entity is reserved immediately, but the value
+    /// cannot be read until the commands have been applied.
+    #[track_caller]
+    fn create_mutable<T>(&mut self, init: T) -> Mutable<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let cell = self.spawn(MutableCell::<T>(init)).id();
+        Mutable {
+            cell,
+            component: None,
+            origin: SignalOrigin::caller(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Create a new [`Mutable`] whose initial value is returned by `init`. The mutable's entity
+    /// is reserved immediately, but `init` is not called until the commands have been applied.
+    #[track_caller]
+    fn create_mutable_with<T, F>(&mut self, init: F) -> Mutable<T>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(&mut EntityWorldMut) -> T + Send + 'static,
+    {
+        let cell = self
+            .spawn_empty()
+            .queue(move |mut entity: EntityWorldMut| {
+                let value = init(&mut entity);
+                entity.insert(MutableCell::<T>(value));
+            })
+            .id();
+        Mutable {
+            cell,
+            component: None,
+            origin: SignalOrigin::caller(),
             marker: PhantomData,
         }
     }
@@ -271,32 +413,39 @@ impl<'w> ReadMutable for DeferredWorld<'w> {
     where
         T: Send + Sync + Copy + 'static,
     {
-        let mutable_entity = self.entity(mutable.cell);
-        mutable_entity.get::<MutableCell<T>>().unwrap().0
+        *expect_mutable_value(self, mutable)
     }
 
     fn read_mutable_clone<T>(&self, mutable: &Mutable<T>) -> T
     where
         T: Send + Sync + Clone + 'static,
     {
-        let mutable_entity = self.entity(mutable.cell);
-        mutable_entity.get::<MutableCell<T>>().unwrap().0.clone()
+        expect_mutable_value(self, mutable).clone()
     }
 
     fn read_mutable_as_ref<T>(&self, mutable: &Mutable<T>) -> &T
     where
         T: Send + Sync + 'static,
     {
-        let mutable_entity = self.entity(mutable.cell);
-        &mutable_entity.get::<MutableCell<T>>().unwrap().0
+        expect_mutable_value(self, mutable)
     }
 
     fn read_mutable_map<T, U, F: Fn(&T) -> U>(&self, mutable: &Mutable<T>, f: F) -> U
     where
         T: Send + Sync + 'static,
     {
-        let mutable_entity = self.entity(mutable.cell);
-        f(&mutable_entity.get::<MutableCell<T>>().unwrap().0)
+        f(expect_mutable_value(self, mutable))
+    }
+
+    fn try_read_mutable_map<T, U, F: FnOnce(&T) -> U>(
+        &self,
+        mutable: &Mutable<T>,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        T: Send + Sync + 'static,
+    {
+        mutable_value(self, mutable).map(f)
     }
 }
 
@@ -307,26 +456,85 @@ impl<'w> WriteMutable for DeferredWorld<'w> {
     where
         T: Send + Sync + PartialEq + 'static,
     {
-        let mut entt = self.entity_mut(mutable);
-        let mut cell = entt.get_mut::<MutableCell<T>>().unwrap();
-        if cell.0 != value {
-            cell.0 = value;
-        }
+        write_mutable_cell(self, mutable, value);
     }
 
     fn update_mutable<T, F: FnOnce(Mut<T>)>(&mut self, mutable: Entity, updater: F)
     where
         T: Send + Sync + 'static,
     {
-        let value = self.get_mut::<MutableCell<T>>(mutable).unwrap();
-        let inner = value.map_unchanged(|v| &mut v.0);
-        (updater)(inner);
+        update_mutable_cell(self, mutable, updater);
+    }
+}
+
+/// Return a reference to the value of a mutable, or an error if its cell is missing.
+fn mutable_value<'w, T>(world: &'w World, mutable: &Mutable<T>) -> Result<&'w T, SignalError>
+where
+    T: Send + Sync + 'static,
+{
+    let Ok(entity) = world.get_entity(mutable.cell) else {
+        return Err(mutable.error(SignalErrorKind::Despawned));
+    };
+    match entity.get::<MutableCell<T>>() {
+        Some(cell) => Ok(&cell.0),
+        None => Err(mutable.error(SignalErrorKind::WrongType)),
+    }
+}
+
+/// Return a reference to the value of a mutable, panicking if its cell is missing.
+fn expect_mutable_value<'w, T>(world: &'w World, mutable: &Mutable<T>) -> &'w T
+where
+    T: Send + Sync + 'static,
+{
+    mutable_value(world, mutable).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Write the value of a mutable cell. If a batch is in progress, the change is recorded in the
+/// batch instead of being reported immediately.
+fn write_mutable_cell<T>(world: &mut DeferredWorld, mutable: Entity, value: T)
+where
+    T: Send + Sync + PartialEq + 'static,
+{
+    let batching = is_batching(world);
+    let component = world.component_id::<MutableCell<T>>().unwrap();
+    let mut entt = world.entity_mut(mutable);
+    let mut cell = entt.get_mut::<MutableCell<T>>().unwrap();
+    if cell.0 != value {
+        if batching {
+            cell.bypass_change_detection().0 = value;
+            record_batch_write(world, mutable, component);
+        } else {
+            cell.0 = value;
+            record_write(world, mutable, component);
+        }
+    }
+}
+
+/// Update a mutable cell in place. If a batch is in progress, any change made by the updater
+/// is recorded in the batch instead of being reported immediately.
+fn update_mutable_cell<T, F: FnOnce(Mut<T>)>(world: &mut DeferredWorld, mutable: Entity, updater: F)
+where
+    T: Send + Sync + 'static,
+{
+    let batching = is_batching(world);
+    let mut value = world.get_mut::<MutableCell<T>>(mutable).unwrap();
+    let last_changed = value.last_changed();
+    (updater)(value.reborrow().map_unchanged(|v| &mut v.0));
+    if value.last_changed() != last_changed {
+        if batching {
+            value.set_last_changed(last_changed);
+        }
+        let component = world.component_id::<MutableCell<T>>().unwrap();
+        match batching {
+            true => record_batch_write(world, mutable, component),
+            false => record_write(world, mutable, component),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Rcx, TrackingScope};
+    use crate::{Ecx, Rcx, TrackingScope};
 
     use super::*;
 
@@ -393,4 +601,53 @@ mod tests {
         assert_eq!(reader.get_clone(&rcx), "Goodbye".to_string());
         assert_eq!(reader2.get(&rcx), 0);
     }
+
+    #[test]
+    fn test_peek_untracked() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let mutable = world.create_mutable::<i32>(1);
+        let signal = mutable.signal();
+
+        // Peeking and untracked reads don't subscribe to the mutable.
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        assert_eq!(mutable.peek(&rcx), 1);
+        assert_eq!(signal.peek(&rcx), 1);
+        assert_eq!(rcx.untracked(|rcx| signal.get(rcx)), 1);
+        let mut ecx = Ecx::new(&mut world, owner, &mut scope);
+        assert_eq!(ecx.untracked(|ecx| signal.get(ecx)), 1);
+        world.increment_change_tick();
+        mutable.set(&mut world, 2);
+        assert!(!scope.dependencies_changed(&world, world.read_change_tick()));
+
+        // A tracked read does.
+        let mut scope = TrackingScope::new(world.change_tick());
+        assert_eq!(signal.get(&Rcx::new(&world, owner, &mut scope)), 2);
+        world.increment_change_tick();
+        mutable.set(&mut world, 3);
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+    }
+
+    #[test]
+    fn test_commands_mutable() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let mutable = world.commands().create_mutable::<u8>(1);
+        assert_eq!(mutable.component, None);
+
+        // Reading before the commands are applied is an error rather than a panic.
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        let err = mutable.try_get(&rcx).unwrap_err();
+        assert_eq!(err.kind(), SignalErrorKind::Despawned);
+
+        // Once applied, reads are tracked as usual.
+        world.flush();
+        let mut scope = TrackingScope::new(world.change_tick());
+        assert_eq!(mutable.get(&Rcx::new(&world, owner, &mut scope)), 1);
+        world.increment_change_tick();
+        mutable.set(&mut world, 2);
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+    }
 }
@@ -0,0 +1,153 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+/// Component which records the entity that owns a reactive object, such as a mutable, derived
+/// or reaction. Owned entities are despawned along with their owner, but unlike children they
+/// are not part of the UI hierarchy.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OwnedBy(pub Entity);
+
+/// Component which lists the entities owned by an entity.
+#[derive(Component, Default, Debug)]
+pub struct OwnedEntities(Vec<Entity>);
+
+impl OwnedEntities {
+    /// Iterate over the owned entities.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+/// Extension trait for setting the owner of an entity.
+pub trait SetOwner {
+    /// Make this entity owned by `owner`, removing it from its previous owner, if any.
+    fn set_owner(&mut self, owner: Entity) -> &mut Self;
+}
+
+impl<'w> SetOwner for EntityWorldMut<'w> {
+    fn set_owner(&mut self, owner: Entity) -> &mut Self {
+        let entity = self.id();
+        let previous = self.get::<OwnedBy>().map(|owned_by| owned_by.0);
+        if previous == Some(owner) {
+            return self;
+        }
+        self.insert(OwnedBy(owner));
+        self.world_scope(|world| {
+            if let Some(previous) = previous {
+                if let Some(mut owned) = world.get_mut::<OwnedEntities>(previous) {
+                    owned.0.retain(|e| *e != entity);
+                }
+            }
+            let mut owner = world.entity_mut(owner);
+            match owner.get_mut::<OwnedEntities>() {
+                Some(mut owned) => owned.0.push(entity),
+                None => {
+                    owner.insert(OwnedEntities(vec![entity]));
+                }
+            }
+        });
+        self
+    }
+}
+
+impl<'a> SetOwner for EntityCommands<'a> {
+    fn set_owner(&mut self, owner: Entity) -> &mut Self {
+        self.queue(move |mut entity: EntityWorldMut| {
+            entity.set_owner(owner);
+        })
+    }
+}
+
+/// Extension trait for despawning the entities owned by an entity.
+pub trait DespawnOwned {
+    /// Despawn all of the entities owned by this entity, without despawning the entity itself.
+    /// This is used when rebuilding the contents of a structural node.
+    fn despawn_owned(&mut self) -> &mut Self;
+}
+
+impl<'w> DespawnOwned for EntityWorldMut<'w> {
+    fn despawn_owned(&mut self) -> &mut Self {
+        let Some(mut owned) = self.get_mut::<OwnedEntities>() else {
+            return self;
+        };
+        let owned = std::mem::take(&mut owned.0);
+        self.world_scope(|world| despawn_entities(world, owned));
+        self
+    }
+}
+
+fn despawn_entities(world: &mut World, entities: Vec<Entity>) {
+    for entity in entities {
+        if let Ok(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+/// Return the entity which owns `entity`, or failing that, its parent.
+pub(crate) fn owner_or_parent(world: &World, entity: Entity) -> Option<Entity> {
+    let entity = world.entity(entity);
+    match entity.get::<OwnedBy>() {
+        Some(owned_by) => Some(owned_by.0),
+        None => entity.get::<Parent>().map(|parent| parent.get()),
+    }
+}
+
+pub(crate) fn register_owner_hooks(world: &mut World) {
+    world
+        .register_component_hooks::<OwnedEntities>()
+        .on_remove(|mut world, entity, _component| {
+            let mut owned = world.get_mut::<OwnedEntities>(entity).unwrap();
+            let owned = std::mem::take(&mut owned.0);
+            if !owned.is_empty() {
+                world
+                    .commands()
+                    .queue(move |world: &mut World| despawn_entities(world, owned));
+            }
+        });
+    world
+        .register_component_hooks::<OwnedBy>()
+        .on_remove(|mut world, entity, _component| {
+            let owner = world.get::<OwnedBy>(entity).unwrap().0;
+            if let Some(mut owned) = world.get_mut::<OwnedEntities>(owner) {
+                owned.0.retain(|e| *e != entity);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_mutable, SignalsPlugin};
+
+    #[test]
+    fn test_owned_entities() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin);
+        let world = app.world_mut();
+        let owner = world.spawn_empty().id();
+        let a = create_mutable(world, owner, 1);
+        let b = create_mutable(world, owner, 2);
+        let nested = create_mutable(world, a.id(), 3);
+        assert!(world.get::<Children>(owner).is_none());
+        assert_eq!(world.get::<OwnedBy>(a.id()), Some(&OwnedBy(owner)));
+        let owned: Vec<Entity> = world.get::<OwnedEntities>(owner).unwrap().iter().collect();
+        assert_eq!(owned, vec![a.id(), b.id()]);
+
+        // Despawning an owned entity removes it from its owner.
+        world.despawn(b.id());
+        let owned: Vec<Entity> = world.get::<OwnedEntities>(owner).unwrap().iter().collect();
+        assert_eq!(owned, vec![a.id()]);
+
+        // Despawning the owner despawns everything it owns, transitively.
+        world.entity_mut(owner).despawn_recursive();
+        assert!(world.get_entity(a.id()).is_err());
+        assert!(world.get_entity(nested.id()).is_err());
+
+        // Owned entities can be despawned without despawning the owner.
+        let owner = world.spawn_empty().id();
+        let c = create_mutable(world, owner, 4);
+        world.entity_mut(owner).despawn_owned();
+        assert!(world.get_entity(c.id()).is_err());
+        assert!(world.get_entity(owner).is_ok());
+    }
+}
@@ -0,0 +1,39 @@
+use bevy::{ecs::world::DeferredWorld, prelude::*};
+
+use crate::{Ecx, Rcx};
+
+/// Trait for contexts which can provide access to the world without tracking. This is used
+/// to read the current value of a signal without subscribing to it, see [`Signal::peek`] and
+/// [`Mutable::peek`].
+///
+/// [`Signal::peek`]: crate::Signal::peek
+/// [`Mutable::peek`]: crate::Mutable::peek
+pub trait PeekWorld {
+    /// Return a reference to the world. Reads through this reference are not added to any
+    /// tracking scope.
+    fn peek_world(&self) -> &World;
+}
+
+impl PeekWorld for World {
+    fn peek_world(&self) -> &World {
+        self
+    }
+}
+
+impl<'w> PeekWorld for DeferredWorld<'w> {
+    fn peek_world(&self) -> &World {
+        self
+    }
+}
+
+impl<'p, 'w> PeekWorld for Rcx<'p, 'w> {
+    fn peek_world(&self) -> &World {
+        self.world
+    }
+}
+
+impl<'p, 'w> PeekWorld for Ecx<'p, 'w> {
+    fn peek_world(&self) -> &World {
+        self.world
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bevy::{
+    ecs::{
+        component::{ComponentId, Tick},
+        world::DeferredWorld,
+    },
+    prelude::*,
+    utils::HashMap,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{create_mutable, mutable::MutableCell, Mutable};
+
+/// A backend which stores the serialized values of persistent mutables, indexed by key.
+pub trait PersistentStore: Send + Sync + 'static {
+    /// Load the serialized value for a key, or `None` if there is no stored value.
+    fn load(&self, key: &str) -> io::Result<Option<String>>;
+
+    /// Store the serialized value for a key.
+    fn save(&self, key: &str, data: &str) -> io::Result<()>;
+}
+
+/// A [`PersistentStore`] which keeps each value in a separate RON file within a directory.
+/// The file name is the key with a `.ron` extension, so keys should be valid file names.
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    /// Construct a new file store in the given directory. The directory is created the first
+    /// time a value is saved.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.ron", key))
+    }
+}
+
+impl PersistentStore for FileStore {
+    fn load(&self, key: &str) -> io::Result<Option<String>> {
+        match std::fs::read_to_string(self.path(key)) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn save(&self, key: &str, data: &str) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        // Write to a temporary file first so that a crash doesn't leave a truncated file.
+        let path = self.path(key);
+        let temp = path.with_extension("ron.tmp");
+        std::fs::write(&temp, data)?;
+        std::fs::rename(temp, path)
+    }
+}
+
+/// A [`PersistentStore`] which keeps values in memory. Clones of a memory store share the
+/// same values, which makes it useful for tests.
+#[derive(Clone, Default)]
+pub struct MemoryStore(Arc<Mutex<HashMap<String, String>>>);
+
+impl MemoryStore {
+    /// Return the serialized value for a key.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    /// Set the serialized value for a key.
+    pub fn insert(&self, key: impl Into<String>, data: impl Into<String>) {
+        self.0.lock().unwrap().insert(key.into(), data.into());
+    }
+}
+
+impl PersistentStore for MemoryStore {
+    fn load(&self, key: &str) -> io::Result<Option<String>> {
+        Ok(self.get(key))
+    }
+
+    fn save(&self, key: &str, data: &str) -> io::Result<()> {
+        self.insert(key, data);
+        Ok(())
+    }
+}
+
+/// Resource which configures where persistent mutables are stored. If this resource is not
+/// present, persistent mutables behave like ordinary mutables: they start with their default
+/// value, and are never saved.
+#[derive(Resource)]
+pub struct PersistentStorage {
+    store: Box<dyn PersistentStore>,
+
+    /// How long a persistent mutable must go without changing before it is saved. This
+    /// avoids writing to the store on every frame while a value is being dragged.
+    pub debounce: Duration,
+}
+
+impl PersistentStorage {
+    /// Construct a new storage configuration using the given backend.
+    pub fn new(store: impl PersistentStore) -> Self {
+        Self {
+            store: Box::new(store),
+            debounce: Duration::from_millis(500),
+        }
+    }
+
+    /// Set the debounce interval.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Store serialized data for a key, with a warning if it cannot be written.
+    fn save(&self, key: &str, data: &str) {
+        if let Err(err) = self.store.save(key, data) {
+            warn!("Failed to save persistent value '{}': {}", key, err);
+        }
+    }
+
+    /// Load and deserialize the value for a key. Returns `None`, with a warning, if the
+    /// stored value cannot be read.
+    fn load<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let data = self
+            .store
+            .load(key)
+            .map_err(|err| warn!("Failed to load persistent value '{}': {}", key, err))
+            .ok()??;
+        ron::from_str(&data)
+            .map_err(|err| warn!("Failed to parse persistent value '{}': {}", key, err))
+            .ok()
+    }
+}
+
+/// Component which marks a mutable cell as persistent.
+#[derive(Component)]
+#[component(on_remove = save_removed_cell)]
+pub(crate) struct PersistentCell {
+    /// The key under which the value is stored.
+    key: String,
+
+    /// The component id of the mutable cell.
+    component: ComponentId,
+
+    /// Function which serializes the value of the mutable cell.
+    serialize: fn(&World, Entity) -> Option<String>,
+
+    /// The tick at which the cell was last checked for changes.
+    last_check: Tick,
+
+    /// If the value has changed but not yet been saved, the time of the most recent change.
+    dirty_since: Option<Duration>,
+}
+
+impl PersistentCell {
+    /// Returns true if the value of the mutable cell has changed since it was last saved.
+    fn is_dirty(&self, world: &World, entity: Entity, this_run: Tick) -> bool {
+        self.dirty_since.is_some()
+            || world
+                .entity(entity)
+                .get_change_ticks_by_id(self.component)
+                .is_some_and(|ct| ct.is_changed(self.last_check, this_run))
+    }
+}
+
+/// Save the value of a persistent mutable which is being despawned, if it has unsaved changes,
+/// so that changes made within the debounce interval are not lost.
+fn save_removed_cell(world: DeferredWorld, entity: Entity, _component: ComponentId) {
+    let Some(storage) = world.get_resource::<PersistentStorage>() else {
+        return;
+    };
+    let cell = world.get::<PersistentCell>(entity).unwrap();
+    if !cell.is_dirty(&world, entity, world.read_change_tick()) {
+        return;
+    }
+    if let Some(data) = (cell.serialize)(&world, entity) {
+        storage.save(&cell.key, &data);
+    }
+}
+
+fn serialize_cell<T: Serialize + Send + Sync + 'static>(
+    world: &World,
+    entity: Entity,
+) -> Option<String> {
+    let cell = world.get::<MutableCell<T>>(entity)?;
+    ron::ser::to_string_pretty(&cell.0, ron::ser::PrettyConfig::default())
+        .map_err(|err| warn!("Failed to serialize persistent value: {}", err))
+        .ok()
+}
+
+/// Function to create a persistent mutable. The initial value is loaded from the
+/// [`PersistentStorage`] if a value has been stored under the given key, otherwise `default`
+/// is used. When the value changes, it is written back to the store.
+#[track_caller]
+pub fn create_persistent_mutable<T>(
+    world: &mut World,
+    parent: Entity,
+    key: impl Into<String>,
+    default: T,
+) -> Mutable<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    let key = key.into();
+    let init = world
+        .get_resource::<PersistentStorage>()
+        .and_then(|storage| storage.load(&key))
+        .unwrap_or(default);
+    let mutable = create_mutable(world, parent, init);
+    let last_check = world.change_tick();
+    let component = mutable.component_id(world).unwrap();
+    world.entity_mut(mutable.cell).insert(PersistentCell {
+        key,
+        component,
+        serialize: serialize_cell::<T>,
+        last_check,
+        dirty_since: None,
+    });
+    mutable
+}
+
+/// System which saves persistent mutables whose values have changed. Values are saved once
+/// they have stopped changing for the debounce interval, or immediately if the app is exiting.
+pub(crate) fn save_persistent_mutables(world: &mut World) {
+    let Some(debounce) = world
+        .get_resource::<PersistentStorage>()
+        .map(|storage| storage.debounce)
+    else {
+        return;
+    };
+    let this_run = world.change_tick();
+    // Without a clock, there is no way to debounce, so save changes right away.
+    let now = world
+        .get_resource::<Time>()
+        .map_or(Duration::ZERO, |time| time.elapsed());
+    let force = world.get_resource::<Time>().is_none()
+        || world
+            .get_resource::<Events<AppExit>>()
+            .is_some_and(|events| !events.is_empty());
+
+    let mut query = world.query::<(Entity, &PersistentCell)>();
+    let mut updates = Vec::new();
+    let mut writes = Vec::new();
+    for (entity, cell) in query.iter(world) {
+        let mut dirty_since = cell.dirty_since;
+        if world
+            .entity(entity)
+            .get_change_ticks_by_id(cell.component)
+            .is_some_and(|ct| ct.is_changed(cell.last_check, this_run))
+        {
+            dirty_since = Some(now);
+        }
+        if dirty_since.is_some_and(|since| force || now.saturating_sub(since) >= debounce) {
+            if let Some(data) = (cell.serialize)(world, entity) {
+                writes.push((cell.key.clone(), data));
+            }
+            dirty_since = None;
+        }
+        updates.push((entity, dirty_since));
+    }
+
+    for (entity, dirty_since) in updates {
+        let mut cell = world.get_mut::<PersistentCell>(entity).unwrap();
+        cell.last_check = this_run;
+        cell.dirty_since = dirty_since;
+    }
+
+    let storage = world.resource::<PersistentStorage>();
+    for (key, data) in writes {
+        storage.save(&key, &data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persistent_mutable() {
+        let mut world = World::default();
+        let store = MemoryStore::default();
+        store.insert("width", "120.0");
+        store.insert("broken", "not a number");
+        world.insert_resource(
+            PersistentStorage::new(store.clone()).with_debounce(Duration::from_secs(1)),
+        );
+        world.init_resource::<Time>();
+        let owner = world.spawn_empty().id();
+
+        // Values are restored from the store, falling back to the default.
+        let width = create_persistent_mutable(&mut world, owner, "width", 100.0f32);
+        let broken = create_persistent_mutable(&mut world, owner, "broken", 1);
+        let expanded = create_persistent_mutable(&mut world, owner, "expanded", false);
+        assert_eq!(width.get(&world), 120.0);
+        assert_eq!(broken.get(&world), 1);
+        assert!(!expanded.get(&world));
+
+        // Unchanged values are not saved.
+        world.increment_change_tick();
+        save_persistent_mutables(&mut world);
+        assert_eq!(store.get("expanded"), None);
+
+        // Changes are saved once the value has settled.
+        world.increment_change_tick();
+        expanded.set(&mut world, true);
+        world.increment_change_tick();
+        save_persistent_mutables(&mut world);
+        assert_eq!(store.get("expanded"), None);
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(600));
+        world.increment_change_tick();
+        save_persistent_mutables(&mut world);
+        assert_eq!(store.get("expanded"), None);
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(600));
+        world.increment_change_tick();
+        save_persistent_mutables(&mut world);
+        assert_eq!(store.get("expanded").as_deref(), Some("true"));
+
+        // A new mutable with the same key picks up the saved value.
+        let restored = create_persistent_mutable(&mut world, owner, "expanded", false);
+        assert!(restored.get(&world));
+    }
+
+    #[test]
+    fn test_save_on_despawn() {
+        let mut world = World::default();
+        let store = MemoryStore::default();
+        world.insert_resource(PersistentStorage::new(store.clone()));
+        world.init_resource::<Time>();
+        let owner = world.spawn_empty().id();
+        let width = create_persistent_mutable(&mut world, owner, "width", 100.0f32);
+        let height = create_persistent_mutable(&mut world, owner, "height", 50.0f32);
+
+        // A change within the debounce interval is saved when the mutable is despawned.
+        world.increment_change_tick();
+        width.set(&mut world, 120.0);
+        world.increment_change_tick();
+        save_persistent_mutables(&mut world);
+        assert_eq!(store.get("width"), None);
+        world.increment_change_tick();
+        world.despawn(width.id());
+        assert_eq!(store.get("width").as_deref(), Some("120.0"));
+
+        // Unchanged values are not saved.
+        world.despawn(height.id());
+        assert_eq!(store.get("height"), None);
+    }
+}
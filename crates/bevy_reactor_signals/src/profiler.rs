@@ -0,0 +1,410 @@
+use std::{fmt::Write, time::Duration};
+
+use bevy::{ecs::component::ComponentId, prelude::*, utils::HashMap};
+
+use crate::{ChangedDependency, ReactionCell, TrackingScope};
+
+/// A resource which, if inserted, records diagnostic information about every reaction that
+/// runs: how long it took, which dependencies caused it to run, and which pass of the
+/// convergence loop it ran in. The recorded data, along with the dependency graph of all
+/// reactions, can be exported in Graphviz DOT or JSON format.
+///
+/// The list of runs is reset each frame, while the per-reaction statistics accumulate until
+/// [`ReactionProfiler::clear`] is called. Reactions which fire every frame can be found by
+/// comparing [`ReactionStats::frames`] with [`ReactionProfiler::frames`].
+#[derive(Resource, Default, Debug)]
+pub struct ReactionProfiler {
+    /// The reactions which ran during the most recent frame, in the order that they ran.
+    pub runs: Vec<ReactionRun>,
+
+    /// The number of passes the convergence loop took during the most recent frame, summed
+    /// over all of the schedules that reactions ran in.
+    pub iterations: usize,
+
+    /// Cumulative statistics for each reaction.
+    pub stats: HashMap<Entity, ReactionStats>,
+
+    /// The number of frames that have been profiled.
+    pub frames: u64,
+}
+
+/// A record of a single reaction running.
+#[derive(Debug, Clone)]
+pub struct ReactionRun {
+    /// The entity which owns the reaction.
+    pub entity: Entity,
+
+    /// How long the reaction took to run.
+    pub duration: Duration,
+
+    /// The pass of the convergence loop in which the reaction ran, starting from zero.
+    pub iteration: usize,
+
+    /// The component and resource dependencies which had changed. If this is empty, the
+    /// reaction was run for some other reason, such as a polled dependency, a deferred
+    /// change, or because it was newly created.
+    pub causes: Vec<ChangedDependency>,
+}
+
+/// Cumulative statistics for a single reaction.
+#[derive(Debug, Clone, Default)]
+pub struct ReactionStats {
+    /// The total number of times the reaction has run.
+    pub runs: u64,
+
+    /// The number of distinct frames in which the reaction has run.
+    pub frames: u64,
+
+    /// The total time spent running the reaction.
+    pub total_duration: Duration,
+
+    /// The most recent frame in which the reaction ran.
+    last_frame: u64,
+}
+
+impl ReactionProfiler {
+    /// Reset all recorded data.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Called at the start of each frame.
+    pub(crate) fn begin_frame(&mut self) {
+        self.frames += 1;
+        self.runs.clear();
+        self.iterations = 0;
+    }
+
+    /// Record that a reaction has run.
+    pub(crate) fn record(&mut self, run: ReactionRun) {
+        let stats = self.stats.entry(run.entity).or_default();
+        stats.runs += 1;
+        stats.total_duration += run.duration;
+        if stats.last_frame != self.frames {
+            stats.last_frame = self.frames;
+            stats.frames += 1;
+        }
+        self.runs.push(run);
+    }
+
+    /// Export the dependency graph of all reactions in Graphviz DOT format. Each reaction is
+    /// a node, labeled with its name and statistics; each dependency is an edge from the
+    /// entity or resource to the reaction, labeled with the component type.
+    pub fn to_dot(&self, world: &World) -> String {
+        let graph = DependencyGraph::new(world);
+        let mut out = String::from("digraph reactions {\n");
+        for node in graph.reactions.iter() {
+            let stats = self.stats.get(&node.entity).cloned().unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "    \"{}\" [shape=box, label=\"{}\\nruns: {}, frames: {}, time: {:?}\"];",
+                node.entity,
+                escape_dot(&node.label()),
+                stats.runs,
+                stats.frames,
+                stats.total_duration
+            );
+        }
+        for edge in graph.edges.iter() {
+            let (source, style) = match edge.source {
+                EdgeSource::Component(entity) => (entity.to_string(), ""),
+                EdgeSource::Resource => (format!("resource:{}", edge.type_name), ""),
+                EdgeSource::Polled => (format!("polled:{}", edge.type_name), ", style=dashed"),
+            };
+            let _ = writeln!(
+                out,
+                "    \"{}\" -> \"{}\" [label=\"{}\"{}];",
+                escape_dot(&source),
+                edge.scope,
+                escape_dot(&edge.type_name),
+                style
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Export the recorded runs, statistics and dependency graph as a JSON document.
+    pub fn to_json(&self, world: &World) -> String {
+        let graph = DependencyGraph::new(world);
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "{{\"frames\":{},\"iterations\":{},\"runs\":[",
+            self.frames, self.iterations
+        );
+        for (i, run) in self.runs.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(
+                out,
+                "{{\"entity\":\"{}\",\"iteration\":{},\"duration_us\":{},\"causes\":[",
+                run.entity,
+                run.iteration,
+                run.duration.as_micros()
+            );
+            for (j, cause) in run.causes.iter().enumerate() {
+                if j > 0 {
+                    out.push(',');
+                }
+                let _ = write!(
+                    out,
+                    "{{\"entity\":{},\"type\":\"{}\"}}",
+                    json_entity(cause.entity),
+                    escape_json(&cause.type_name)
+                );
+            }
+            out.push_str("]}");
+        }
+        out.push_str("],\"reactions\":[");
+        for (i, node) in graph.reactions.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let stats = self.stats.get(&node.entity).cloned().unwrap_or_default();
+            let _ = write!(
+                out,
+                "{{\"entity\":\"{}\",\"name\":{},\"runs\":{},\"frames\":{},\"total_us\":{}}}",
+                node.entity,
+                node.name
+                    .as_ref()
+                    .map_or_else(|| "null".to_string(), |n| format!("\"{}\"", escape_json(n))),
+                stats.runs,
+                stats.frames,
+                stats.total_duration.as_micros()
+            );
+        }
+        out.push_str("],\"edges\":[");
+        for (i, edge) in graph.edges.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let (from, kind) = match edge.source {
+                EdgeSource::Component(entity) => (json_entity(Some(entity)), "component"),
+                EdgeSource::Resource => (json_entity(None), "resource"),
+                EdgeSource::Polled => (json_entity(None), "polled"),
+            };
+            let _ = write!(
+                out,
+                "{{\"from\":{},\"kind\":\"{}\",\"type\":\"{}\",\"to\":\"{}\"}}",
+                from,
+                kind,
+                escape_json(&edge.type_name),
+                edge.scope
+            );
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+/// A reaction in the dependency graph.
+struct ReactionNode {
+    entity: Entity,
+    name: Option<String>,
+}
+
+impl ReactionNode {
+    fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} ({})", name, self.entity),
+            None => self.entity.to_string(),
+        }
+    }
+}
+
+/// The kind of dependency an edge comes from.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum EdgeSource {
+    /// A component on the given entity.
+    Component(Entity),
+    Resource,
+    Polled,
+}
+
+/// An edge from a dependency to the reaction which depends on it.
+struct DependencyEdge {
+    source: EdgeSource,
+    type_name: String,
+    scope: Entity,
+}
+
+/// Snapshot of the dependencies of every reaction in the world.
+struct DependencyGraph {
+    reactions: Vec<ReactionNode>,
+    edges: Vec<DependencyEdge>,
+}
+
+impl DependencyGraph {
+    fn new(world: &World) -> Self {
+        let type_name = |id: ComponentId| {
+            world
+                .components()
+                .get_info(id)
+                .map_or_else(|| format!("{:?}", id), |info| info.name().to_string())
+        };
+        let mut reactions = Vec::new();
+        let mut edges = Vec::new();
+        for entity in world.iter_entities() {
+            if !entity.contains::<ReactionCell>() {
+                continue;
+            }
+            let Some(scope) = entity.get::<TrackingScope>() else {
+                continue;
+            };
+            reactions.push(ReactionNode {
+                entity: entity.id(),
+                name: entity.get::<Name>().map(|name| name.to_string()),
+            });
+            for (dep_entity, component) in scope.component_dependencies() {
+                edges.push(DependencyEdge {
+                    source: EdgeSource::Component(*dep_entity),
+                    type_name: type_name(*component),
+                    scope: entity.id(),
+                });
+            }
+            for resource in scope.resource_dependencies() {
+                edges.push(DependencyEdge {
+                    source: EdgeSource::Resource,
+                    type_name: type_name(*resource),
+                    scope: entity.id(),
+                });
+            }
+            for polled in scope.polled_dependencies() {
+                edges.push(DependencyEdge {
+                    source: EdgeSource::Polled,
+                    type_name: polled.name().to_string(),
+                    scope: entity.id(),
+                });
+            }
+        }
+        reactions.sort_unstable_by_key(|node| node.entity);
+        edges.sort_by_key(|edge| (edge.scope, edge.source));
+        Self { reactions, edges }
+    }
+}
+
+fn json_entity(entity: Option<Entity>) -> String {
+    entity.map_or_else(|| "null".to_string(), |e| format!("\"{}\"", e))
+}
+
+/// Escape a string for use inside a quoted JSON string.
+fn escape_json(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape a string for use inside a quoted DOT string. DOT has no escape for arbitrary
+/// characters, so newlines become line breaks and other control characters are dropped.
+fn escape_dot(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::component::Tick;
+
+    use super::*;
+    use crate::{
+        dependency_index::DependencyIndex,
+        tracking_scope::{register_tracking_scope_hooks, run_reactions},
+        PolledDependency, Reaction,
+    };
+
+    #[derive(Resource, Default)]
+    struct Counter(u32);
+
+    struct TrackCounter;
+
+    impl Reaction for TrackCounter {
+        fn react(&mut self, _owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+            tracking.track_resource::<Counter>(world);
+            tracking.track_polled(std::sync::Arc::new(Never));
+        }
+    }
+
+    /// Polled dependency which never changes.
+    struct Never;
+
+    impl PolledDependency for Never {
+        fn changed(&self, _world: &World, _last_run: Tick, _this_run: Tick) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_profiler() {
+        let mut world = World::default();
+        register_tracking_scope_hooks(&mut world);
+        world.init_resource::<DependencyIndex>();
+        world.init_resource::<ReactionProfiler>();
+        world.init_resource::<Counter>();
+        world.increment_change_tick();
+
+        let mut scope = TrackingScope::new(world.change_tick());
+        let owner = world.spawn(Name::new("Watcher")).id();
+        TrackCounter.react(owner, &mut world, &mut scope);
+        world
+            .entity_mut(owner)
+            .insert((scope, ReactionCell::new(TrackCounter)));
+
+        world.increment_change_tick();
+        world.resource_mut::<Counter>().0 += 1;
+        world.increment_change_tick();
+        run_reactions(&mut world);
+
+        let profiler = world.resource::<ReactionProfiler>();
+        assert_eq!(profiler.frames, 1);
+        assert_eq!(profiler.iterations, 1);
+        assert_eq!(profiler.runs.len(), 1);
+        assert_eq!(profiler.runs[0].entity, owner);
+        assert_eq!(profiler.runs[0].causes.len(), 1);
+        assert!(profiler.runs[0].causes[0].type_name.ends_with("Counter"));
+        assert_eq!(profiler.stats[&owner].runs, 1);
+
+        let dot = profiler.to_dot(&world);
+        assert!(dot.contains("Watcher"));
+        assert!(dot.contains(&format!("-> \"{}\"", owner)));
+        assert!(dot.contains("\"polled:bevy_reactor_signals::profiler::tests::Never\""));
+        let json = profiler.to_json(&world);
+        assert!(json.starts_with("{\"frames\":1,\"iterations\":1,"));
+        assert!(json.contains("\"name\":\"Watcher\""));
+        assert!(json.contains("\"kind\":\"polled\""));
+
+        // Nothing changed, so nothing runs on the next frame.
+        world.increment_change_tick();
+        run_reactions(&mut world);
+        let profiler = world.resource::<ReactionProfiler>();
+        assert_eq!(profiler.frames, 2);
+        assert!(profiler.runs.is_empty());
+        assert_eq!(profiler.stats[&owner].frames, 1);
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape_json("a\"b\\c\nd\u{1}"), "a\\\"b\\\\c\\nd\\u0001");
+        assert_eq!(escape_dot("a\"b\\c\nd\u{1}"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    ecs::{
+        component::Tick,
+        query::{QueryFilter, ROQueryItem, ReadOnlyQueryData},
+    },
+    prelude::*,
+};
+
+use crate::{tracking_scope::PolledDependency, SetOwner, TrackingScope};
+
+/// Contains the state of a reactive query.
+#[derive(Component)]
+pub(crate) struct QueryCell<D: ReadOnlyQueryData + 'static, F: QueryFilter + 'static>(
+    pub(crate) Arc<Mutex<QueryState<(Entity, D), F>>>,
+);
+
+/// Contains a reference to a reactive ECS query. Reading the query from a reactive context
+/// subscribes to changes in the set of matching entities, as well as changes to any of the
+/// components read by the query on those entities.
+pub struct ReactiveQuery<D, F = ()> {
+    /// The entity that holds the query state.
+    pub(crate) cell: Entity,
+
+    /// Marker
+    pub(crate) marker: PhantomData<fn() -> (D, F)>,
+}
+
+impl<D, F> ReactiveQuery<D, F> {
+    /// The entity that holds the query state.
+    pub fn id(&self) -> Entity {
+        self.cell
+    }
+}
+
+impl<D, F> Copy for ReactiveQuery<D, F> {}
+impl<D, F> Clone for ReactiveQuery<D, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// Function to create a reactive query.
+pub fn create_query<D: ReadOnlyQueryData + 'static, F: QueryFilter + 'static>(
+    world: &mut World,
+    parent: Entity,
+) -> ReactiveQuery<D, F> {
+    let state = world.query_filtered::<(Entity, D), F>();
+    let cell = world
+        .spawn(QueryCell::<D, F>(Arc::new(Mutex::new(state))))
+        .set_owner(parent)
+        .id();
+    ReactiveQuery {
+        cell,
+        marker: PhantomData,
+    }
+}
+
+/// Run the query and add it as a dependency of the given tracking scope.
+pub(crate) fn read_query_with_scope<
+    'w,
+    D: ReadOnlyQueryData + 'static,
+    F: QueryFilter + 'static,
+>(
+    world: &'w World,
+    query: &ReactiveQuery<D, F>,
+    scope: &mut TrackingScope,
+) -> Vec<ROQueryItem<'w, D>> {
+    let state_ref = match world.get::<QueryCell<D, F>>(query.cell) {
+        Some(cell) => cell.0.clone(),
+        None => panic!("No query found for {:?}", query.cell),
+    };
+    let mut entities = Vec::new();
+    let mut items = Vec::new();
+    {
+        let mut state = state_ref.lock().unwrap();
+        state.update_archetypes(world);
+        for (entity, item) in state.iter_manual(world) {
+            entities.push(entity);
+            items.push(item);
+        }
+    }
+    scope.track_polled(Arc::new(QueryDependency {
+        state: state_ref,
+        entities,
+    }));
+    items
+}
+
+/// Dependency on the result set of a query.
+struct QueryDependency<D: ReadOnlyQueryData + 'static, F: QueryFilter + 'static> {
+    state: Arc<Mutex<QueryState<(Entity, D), F>>>,
+
+    /// The entities which matched the query when it was read.
+    entities: Vec<Entity>,
+}
+
+impl<D: ReadOnlyQueryData + 'static, F: QueryFilter + 'static> PolledDependency
+    for QueryDependency<D, F>
+{
+    fn changed(&self, world: &World, last_run: Tick, this_run: Tick) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.update_archetypes(world);
+        let state = &*state;
+
+        // If none of the previous entities has left the result set, then the result set is
+        // unchanged exactly when its size is unchanged. For archetypal filters the size is
+        // the total size of the matched archetypes, so no entities need to be visited.
+        let count = match F::IS_ARCHETYPAL {
+            true => state
+                .matched_archetypes()
+                .map(|id| world.archetypes()[id].len())
+                .sum(),
+            false => state.iter_manual(world).count(),
+        };
+        if count != self.entities.len()
+            || self
+                .entities
+                .iter()
+                .any(|e| state.get_manual(world, *e).is_err())
+        {
+            return true;
+        }
+
+        // Check whether any of the components read by the query have changed.
+        let access = state.component_access().access();
+        self.entities.iter().any(|e| {
+            let entity = world.entity(*e);
+            let changed = entity.archetype().components().any(|c| {
+                access.has_component_read(c)
+                    && entity
+                        .get_change_ticks_by_id(c)
+                        .is_some_and(|ct| ct.is_changed(last_run, this_run))
+            });
+            changed
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Rcx;
+
+    use super::*;
+
+    #[derive(Component)]
+    struct Selected(u32);
+
+    #[derive(Component)]
+    struct Hidden;
+
+    #[test]
+    fn test_query_deps_changed() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let e1 = world.spawn(Selected(1)).id();
+        let query = create_query::<&Selected, ()>(&mut world, owner);
+
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        let items: Vec<u32> = rcx.read_query(&query).iter().map(|s| s.0).collect();
+        assert_eq!(items, vec![1]);
+        assert!(!scope.dependencies_changed(&world, world.read_change_tick()));
+
+        // Entity entering the result set
+        world.increment_change_tick();
+        let e2 = world.spawn(Selected(2)).id();
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+
+        // Entity leaving the result set
+        let mut scope = TrackingScope::new(world.change_tick());
+        Rcx::new(&world, owner, &mut scope).read_query(&query);
+        world.increment_change_tick();
+        world.entity_mut(e2).remove::<Selected>();
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+
+        // Tracked component changing on a matching entity
+        let mut scope = TrackingScope::new(world.change_tick());
+        Rcx::new(&world, owner, &mut scope).read_query(&query);
+        assert!(!scope.dependencies_changed(&world, world.read_change_tick()));
+        world.increment_change_tick();
+        world.get_mut::<Selected>(e1).unwrap().0 = 3;
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+    }
+
+    #[test]
+    fn test_query_deps_swapped() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let e1 = world.spawn(Selected(1)).id();
+        let query = create_query::<&Selected, Without<Hidden>>(&mut world, owner);
+        let mut scope = TrackingScope::new(world.change_tick());
+        Rcx::new(&world, owner, &mut scope).read_query(&query);
+
+        // One entity leaving and another entering leaves the size of the result set unchanged.
+        world.increment_change_tick();
+        world.entity_mut(e1).insert(Hidden);
+        world.spawn(Selected(2));
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+        let items: Vec<u32> = Rcx::new(&world, owner, &mut scope)
+            .read_query(&query)
+            .iter()
+            .map(|s| s.0)
+            .collect();
+        assert_eq!(items, vec![2]);
+    }
+}
@@ -1,12 +1,19 @@
 use std::cell::RefCell;
 
 use bevy::{
-    ecs::world::DeferredWorld,
-    prelude::{Component, Entity, Parent, Resource, World},
+    asset::{Asset, Assets, Handle},
+    ecs::{
+        query::{QueryFilter, ROQueryItem, ReadOnlyQueryData},
+        schedule::ScheduleLabel,
+        world::DeferredWorld,
+    },
+    prelude::{Component, Entity, Event, Resource, State, States, World},
 };
 
 use crate::{
-    derived::ReadDerivedInternal, Derived, Mutable, ReadDerived, ReadMutable, TrackingScope,
+    assets::track_asset, derived::ReadDerivedInternal, events::read_events_for,
+    owner::owner_or_parent, query::read_query_with_scope, Derived, Mutable, ReactiveQuery,
+    ReadDerived, ReadMutable, SignalError, TrackingScope,
 };
 
 /// Immutable reactive context, used for reactive closures such as derived signals.
@@ -45,6 +52,12 @@ impl<'p, 'w> Rcx<'p, 'w> {
         self.world.resource::<T>()
     }
 
+    /// Return the current value of the state `S`. Calling this function adds the state as a
+    /// dependency of the current tracking scope, so that the scope reacts to state transitions.
+    pub fn read_state<S: States>(&self) -> S {
+        self.read_resource::<State<S>>().get().clone()
+    }
+
     /// Return a reference to the Component `C` on the given entity. Calling this function
     /// adds the component as a dependency of the current tracking scope.
     pub fn read_component<C: Component>(&self, entity: Entity) -> Option<&C> {
@@ -54,9 +67,37 @@ impl<'p, 'w> Rcx<'p, 'w> {
         self.world.entity(entity).get::<C>()
     }
 
+    /// Return the items matching a reactive query. Calling this function adds the query as a
+    /// dependency of the current tracking scope: the scope will react when entities enter
+    /// or leave the query's result set, or when any of the components read by the query
+    /// change on matching entities.
+    pub fn read_query<D: ReadOnlyQueryData + 'static, F: QueryFilter + 'static>(
+        &self,
+        query: &ReactiveQuery<D, F>,
+    ) -> Vec<ROQueryItem<'w, D>> {
+        read_query_with_scope(self.world, query, &mut self.tracking.borrow_mut())
+    }
+
+    /// Return the events of type `E` which have arrived since this reaction last read them.
+    /// Calling this function causes the current tracking scope to be marked as changed when
+    /// new events arrive. The event type must be registered with
+    /// [`add_reactive_event`](crate::AddReactiveEvent::add_reactive_event).
+    pub fn read_events<E: Event + Clone>(&self) -> Vec<E> {
+        read_events_for(self.world, &mut self.tracking.borrow_mut())
+    }
+
+    /// Return a reference to the asset referred to by `handle`, or `None` if it isn't loaded.
+    /// Calling this function causes the current tracking scope to be marked as changed when
+    /// the asset is loaded, modified or removed. The asset type must be registered with
+    /// [`add_reactive_asset`](crate::AddReactiveAsset::add_reactive_asset).
+    pub fn read_asset<A: Asset>(&self, handle: &Handle<A>) -> Option<&A> {
+        track_asset(self.world, handle.id(), &mut self.tracking.borrow_mut());
+        self.world.resource::<Assets<A>>().get(handle)
+    }
+
     /// Return a reference to the Component `C` on the owner entity of the current
-    /// context, or one of it's ancestors. This searches up the entity tree until it finds
-    /// a component of the given type.
+    /// context, or one of it's ancestors. This searches up the chain of owners and parents until
+    /// it finds a component of the given type.
     pub fn use_inherited_component<C: Component>(&self) -> Option<&C> {
         let mut entity = self.owner;
         loop {
@@ -64,8 +105,8 @@ impl<'p, 'w> Rcx<'p, 'w> {
             if ec.is_some() {
                 return ec;
             }
-            match self.world.entity(entity).get::<Parent>() {
-                Some(parent) => entity = **parent,
+            match owner_or_parent(self.world, entity) {
+                Some(parent) => entity = parent,
                 _ => return None,
             }
         }
@@ -77,11 +118,42 @@ impl<'p, 'w> Rcx<'p, 'w> {
         self.tracking.borrow_mut().set_deferred_change();
     }
 
+    /// Run a closure with a context that doesn't add any dependencies to the current tracking
+    /// scope. This can be used to read the current value of a signal, or a resource, without
+    /// reacting when it changes. Cleanup functions registered within the closure are still run
+    /// before the next reaction.
+    pub fn untracked<R>(&self, f: impl FnOnce(&Rcx) -> R) -> R {
+        let mut scope = TrackingScope::new(self.tracking.borrow().tick);
+        let result = f(&Rcx::new(self.world, self.owner, &mut scope));
+        self.tracking
+            .borrow_mut()
+            .cleanups
+            .append(&mut scope.cleanups);
+        result
+    }
+
+    /// Run the current tracking scope in the given schedule rather than the default one.
+    /// This is useful for reactions which depend on data computed later in the frame, such
+    /// as UI layout. See [`TrackingScope::set_schedule`].
+    pub fn set_schedule(&self, schedule: impl ScheduleLabel) {
+        self.tracking.borrow_mut().set_schedule(schedule);
+    }
+
     /// Add a cleanup function which is run once before the next reaction, or when the owner
     /// entity for this context is despawned.
     pub fn on_cleanup(&mut self, cleanup: impl FnOnce(&mut DeferredWorld) + Send + Sync + 'static) {
         self.tracking.borrow_mut().add_cleanup(cleanup);
     }
+
+    /// Add a mutable which has just been read to the current tracking scope.
+    fn track_mutable<T: Send + Sync + 'static>(&self, mutable: &Mutable<T>) {
+        // The cell's component is registered, since the cell could be read.
+        if let Some(component) = mutable.component_id(self.world) {
+            self.tracking
+                .borrow_mut()
+                .track_component_id(mutable.cell, component);
+        }
+    }
 }
 
 impl<'p, 'w> ReadMutable for Rcx<'p, 'w> {
@@ -89,40 +161,49 @@ impl<'p, 'w> ReadMutable for Rcx<'p, 'w> {
     where
         T: Send + Sync + Copy + 'static,
     {
-        self.tracking
-            .borrow_mut()
-            .track_component_id(mutable.cell, mutable.component);
-        self.world.read_mutable(mutable)
+        let value = self.world.read_mutable(mutable);
+        self.track_mutable(mutable);
+        value
     }
 
     fn read_mutable_clone<T>(&self, mutable: &Mutable<T>) -> T
     where
         T: Send + Sync + Clone + 'static,
     {
-        self.tracking
-            .borrow_mut()
-            .track_component_id(mutable.cell, mutable.component);
-        self.world.read_mutable_clone(mutable)
+        let value = self.world.read_mutable_clone(mutable);
+        self.track_mutable(mutable);
+        value
     }
 
     fn read_mutable_as_ref<T>(&self, mutable: &Mutable<T>) -> &T
     where
         T: Send + Sync + 'static,
     {
-        self.tracking
-            .borrow_mut()
-            .track_component_id(mutable.cell, mutable.component);
-        self.world.read_mutable_as_ref(mutable)
+        let value = self.world.read_mutable_as_ref(mutable);
+        self.track_mutable(mutable);
+        value
     }
 
     fn read_mutable_map<T, U, F: Fn(&T) -> U>(&self, mutable: &Mutable<T>, f: F) -> U
     where
         T: Send + Sync + 'static,
     {
-        self.tracking
-            .borrow_mut()
-            .track_component_id(mutable.cell, mutable.component);
-        self.world.read_mutable_map(mutable, f)
+        let value = self.world.read_mutable_map(mutable, f);
+        self.track_mutable(mutable);
+        value
+    }
+
+    fn try_read_mutable_map<T, U, F: FnOnce(&T) -> U>(
+        &self,
+        mutable: &Mutable<T>,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        T: Send + Sync + 'static,
+    {
+        let value = self.world.try_read_mutable_map(mutable, f)?;
+        self.track_mutable(mutable);
+        Ok(value)
     }
 }
 
@@ -150,4 +231,16 @@ impl<'p, 'w> ReadDerived for Rcx<'p, 'w> {
         self.world
             .read_derived_map_with_scope(derived.id, &mut self.tracking.borrow_mut(), f)
     }
+
+    fn try_read_derived_map<R, U, F: FnOnce(&R) -> U>(
+        &self,
+        derived: &Derived<R>,
+        f: F,
+    ) -> Result<U, SignalError>
+    where
+        R: Send + Sync + 'static,
+    {
+        self.world
+            .try_read_derived_map_with_scope(derived, &mut self.tracking.borrow_mut(), f)
+    }
 }
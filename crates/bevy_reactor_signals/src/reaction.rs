@@ -19,6 +19,27 @@ pub trait Reaction {
     /// - `world`: The Bevy world.
     /// - `tracking`: The tracking scope for the reaction.
     fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope);
+
+    /// The kind of reaction, which determines the order in which reactions are run when
+    /// several of them have changed. The default is [`ReactionKind::Effect`].
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Effect
+    }
+}
+
+/// Classifies reactions for the purpose of ordering. When multiple reactions need to run,
+/// memos are run first (so that effects never see a stale memoized value), followed by
+/// effects, followed by structural reactions which rebuild parts of the entity hierarchy.
+/// Within each kind, reactions are run in order of dependency depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ReactionKind {
+    /// A reaction which computes a value that other reactions depend on.
+    Memo,
+    /// A reaction which produces side effects.
+    #[default]
+    Effect,
+    /// A reaction which despawns and rebuilds entities, such as a conditional or a list.
+    Structural,
 }
 
 /// Component which contains a reference to a reaction. Generally the entity will also
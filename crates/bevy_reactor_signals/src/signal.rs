@@ -1,4 +1,4 @@
-use crate::{derived::ReadDerived, mutable::ReadMutable, Derived, Mutable};
+use crate::{derived::ReadDerived, mutable::ReadMutable, Derived, Mutable, PeekWorld, SignalError};
 
 /// What type of reactive node underlies this signal. "Signals" in this framework represent
 /// any kind of reactive data source, including mutable variables, derived signals, and memoized
@@ -40,13 +40,29 @@ where
             Signal::Constant(value) => *value,
         }
     }
+
+    /// Read the value of the signal using Copy semantics, returning an error if the underlying
+    /// mutable or derived has been despawned.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Derived::try_map`](crate::Derived::try_map).
+    pub fn try_get<R: ReadMutable + ReadDerived>(&self, rc: &R) -> Result<T, SignalError> {
+        self.try_map(rc, |value| *value)
+    }
+
+    /// Read the value of the signal using Copy semantics, without adding it as a dependency
+    /// of the current tracking scope.
+    pub fn peek<R: PeekWorld>(&self, rc: &R) -> T {
+        self.get(rc.peek_world())
+    }
 }
 
 impl<T> Signal<T>
 where
     T: Clone + Send + Sync + 'static,
 {
-    /// Read the value of the signal using Copy semantics.
+    /// Read the value of the signal using Clone semantics.
     pub fn get_clone<R: ReadMutable + ReadDerived>(&self, rc: &R) -> T {
         match self {
             Signal::Mutable(mutable) => rc.read_mutable_clone(mutable),
@@ -54,6 +70,22 @@ where
             Signal::Constant(value) => value.clone(),
         }
     }
+
+    /// Read the value of the signal using Clone semantics, returning an error if the underlying
+    /// mutable or derived has been despawned.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Derived::try_map`](crate::Derived::try_map).
+    pub fn try_get_clone<R: ReadMutable + ReadDerived>(&self, rc: &R) -> Result<T, SignalError> {
+        self.try_map(rc, T::clone)
+    }
+
+    /// Read the value of the signal using Clone semantics, without adding it as a dependency
+    /// of the current tracking scope.
+    pub fn peek_clone<R: PeekWorld>(&self, rc: &R) -> T {
+        self.get_clone(rc.peek_world())
+    }
 }
 
 impl<T> Signal<T>
@@ -68,6 +100,24 @@ where
             Signal::Constant(value) => f(value),
         }
     }
+
+    /// Read the value of the signal using a mapping function, returning an error if the
+    /// underlying mutable or derived has been despawned.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Derived::try_map`](crate::Derived::try_map).
+    pub fn try_map<R: ReadMutable + ReadDerived, U, F: FnOnce(&T) -> U>(
+        &self,
+        rc: &R,
+        f: F,
+    ) -> Result<U, SignalError> {
+        match self {
+            Signal::Mutable(mutable) => rc.try_read_mutable_map(mutable, f),
+            Signal::Derived(derived) => rc.try_read_derived_map(derived, f),
+            Signal::Constant(value) => Ok(f(value)),
+        }
+    }
 }
 
 /// Implement default if T has a default.
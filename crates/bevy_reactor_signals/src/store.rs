@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+
+/// Derive macro which generates a fine-grained reactive store for a struct. See [`Store`].
+pub use bevy_reactor_macros::Store;
+
+/// Trait for types which can be held in a reactive store, in which each field is held in its
+/// own [`Mutable`](crate::Mutable). Reading a field through the store only adds that field to
+/// the current tracking scope, so reactions which depend on one field are not re-run when a
+/// different field changes.
+///
+/// This trait is normally implemented using `#[derive(Store)]`, which generates a handle type
+/// named after the struct with a `Store` suffix. For each field `foo`, the handle has a
+/// `foo(cx)` accessor which reads the field, a `set_foo(cx, value)` method, and a
+/// `foo_mutable()` method which returns the underlying mutable.
+///
+/// Fields whose type also derives `Store` can be annotated with `#[store(nested)]`, in which
+/// case `foo()` returns the nested store. `Vec` fields whose elements derive `Store` can be
+/// annotated with `#[store(vec)]`, in which case `foo(cx)` returns the element stores, and the
+/// handle has `push_foo` and `remove_foo` methods.
+pub trait Store: Sized {
+    /// The handle type which provides access to the fields of the store.
+    type Handle: StoreHandle + Copy + PartialEq + Send + Sync + 'static;
+
+    /// Create a store, owned by `parent`, which holds the fields of this value.
+    fn create_store(self, world: &mut World, parent: Entity) -> Self::Handle;
+}
+
+/// Trait implemented by the handles of reactive stores.
+pub trait StoreHandle {
+    /// The id of the entity which owns the store's fields. Despawning this entity recursively
+    /// despawns the store.
+    fn id(&self) -> Entity;
+}
+
+/// Create a store, owned by `parent`, which holds the fields of `value`.
+#[track_caller]
+pub fn create_store<S: Store>(world: &mut World, parent: Entity, value: S) -> S::Handle {
+    value.create_store(world, parent)
+}
+
+/// Items used by the code generated by `#[derive(Store)]`.
+#[doc(hidden)]
+pub mod __private {
+    pub use bevy::prelude::{Entity, Mut, World};
+
+    use bevy::{core::Name, prelude::*};
+
+    use super::StoreHandle;
+    use crate::SetOwner;
+
+    /// Spawn the entity which owns the fields of a store.
+    pub fn spawn_store(world: &mut World, parent: Entity) -> Entity {
+        world.spawn(Name::new("Store")).set_owner(parent).id()
+    }
+
+    /// Despawn a store along with all of its fields.
+    pub fn despawn_store<H: StoreHandle>(world: &mut World, store: &H) {
+        if let Ok(entity) = world.get_entity_mut(store.id()) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rcx, TrackingScope};
+
+    #[derive(Store)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Store)]
+    struct Todo {
+        title: String,
+        done: bool,
+    }
+
+    /// Field names which coincide with names used by the generated code.
+    #[derive(Store)]
+    struct Record {
+        entity: u32,
+        world: String,
+        parent: bool,
+    }
+
+    #[derive(Store)]
+    struct Document {
+        name: String,
+        #[store(nested)]
+        cursor: Position,
+        #[store(vec)]
+        todos: Vec<Todo>,
+    }
+
+    #[test]
+    fn test_store() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let doc = create_store(
+            &mut world,
+            owner,
+            Document {
+                name: "notes".into(),
+                cursor: Position { x: 0., y: 0. },
+                todos: vec![Todo {
+                    title: "write".into(),
+                    done: false,
+                }],
+            },
+        );
+
+        // Reading a field only tracks that field.
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        assert_eq!(doc.name(&rcx), "notes");
+        assert_eq!(doc.cursor().x(&rcx), 0.);
+        world.increment_change_tick();
+        doc.cursor().set_y(&mut world, 2.);
+        doc.set_name(&mut world, "notes".into());
+        assert!(!scope.dependencies_changed(&world, world.read_change_tick()));
+        doc.cursor().set_x(&mut world, 1.);
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+        assert_eq!(doc.cursor().y(&world), 2.);
+
+        // Changing an element doesn't change the list of elements.
+        let mut scope = TrackingScope::new(world.change_tick());
+        let rcx = Rcx::new(&world, owner, &mut scope);
+        let todos = doc.todos(&rcx);
+        assert_eq!(todos.len(), 1);
+        world.increment_change_tick();
+        todos[0].set_done(&mut world, true);
+        assert!(todos[0].done(&world));
+        assert_eq!(todos[0].title(&world), "write");
+        assert!(!scope.dependencies_changed(&world, world.read_change_tick()));
+
+        let added = doc.push_todos(
+            &mut world,
+            Todo {
+                title: "test".into(),
+                done: false,
+            },
+        );
+        assert!(scope.dependencies_changed(&world, world.read_change_tick()));
+        assert_eq!(doc.todos(&world), vec![todos[0], added]);
+        doc.remove_todos(&mut world, 0);
+        assert_eq!(doc.todos(&world), vec![added]);
+        assert!(world.get_entity(todos[0].id()).is_err());
+    }
+
+    #[test]
+    fn test_store_reserved_names() {
+        let mut world = World::default();
+        let owner = world.spawn_empty().id();
+        let record = create_store(
+            &mut world,
+            owner,
+            Record {
+                entity: 7,
+                world: "earth".into(),
+                parent: true,
+            },
+        );
+        assert_eq!(record.entity(&world), 7);
+        assert_eq!(record.world(&world), "earth");
+        assert!(record.parent(&world));
+        assert_ne!(record.id(), owner);
+    }
+}
@@ -0,0 +1,282 @@
+use std::{sync::Arc, time::Duration};
+
+use bevy::{core::Name, ecs::component::Tick, prelude::*};
+
+use crate::{
+    create_mutable, tracking_scope::PolledDependency, Rcx, Reaction, ReactionCell, ReactionKind,
+    Signal, TrackingScope, WriteMutable,
+};
+
+/// Returns the elapsed time of the world's clock, or zero if there is no [`Time`] resource.
+fn now(world: &World) -> Duration {
+    world
+        .get_resource::<Time>()
+        .map_or(Duration::ZERO, |time| time.elapsed())
+}
+
+/// Dependency which changes when the world's clock reaches a deadline.
+struct Deadline(Duration);
+
+impl PolledDependency for Deadline {
+    fn changed(&self, world: &World, _last_run: Tick, _this_run: Tick) -> bool {
+        now(world) >= self.0
+    }
+}
+
+/// Whether a [`RateLimitReaction`] debounces or throttles its input.
+enum RateLimit {
+    Debounce,
+    Throttle { last_emit: Option<Duration> },
+}
+
+/// Reaction which copies its input to its output, limiting how often the output changes.
+struct RateLimitReaction<T> {
+    source: Signal<T>,
+    delay: Duration,
+    mode: RateLimit,
+
+    /// The most recent value of the input.
+    value: T,
+
+    /// The time at which `value` should be copied to the output, if it hasn't been yet.
+    pending: Option<Duration>,
+}
+
+impl<T: PartialEq + Clone + Send + Sync + 'static> Reaction for RateLimitReaction<T> {
+    fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+        let value = self.source.get_clone(&Rcx::new(world, owner, tracking));
+        let now = now(world);
+        if value != self.value {
+            self.value = value;
+            self.pending = Some(match self.mode {
+                RateLimit::Debounce => now + self.delay,
+                RateLimit::Throttle { last_emit } => {
+                    last_emit.map_or(now, |last| (last + self.delay).max(now))
+                }
+            });
+        }
+        if let Some(deadline) = self.pending {
+            if now >= deadline {
+                self.pending = None;
+                if let RateLimit::Throttle { last_emit } = &mut self.mode {
+                    *last_emit = Some(now);
+                }
+                world.write_mutable(owner, self.value.clone());
+            } else {
+                tracking.track_polled(Arc::new(Deadline(deadline)));
+            }
+        }
+    }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Memo
+    }
+}
+
+/// Reaction which counts the number of periods which have elapsed.
+struct IntervalReaction {
+    start: Duration,
+    period: Duration,
+    count: u64,
+}
+
+impl Reaction for IntervalReaction {
+    fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+        let elapsed = now(world).saturating_sub(self.start);
+        let count = (elapsed.as_secs_f64() / self.period.as_secs_f64()).floor() as u64;
+        if count != self.count {
+            self.count = count;
+            world.write_mutable(owner, count);
+        }
+        tracking.track_polled(Arc::new(Deadline(
+            self.start + self.period.mul_f64((count + 1) as f64),
+        )));
+    }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Memo
+    }
+}
+
+/// Reaction which becomes true once a deadline has passed.
+struct TimeoutReaction {
+    deadline: Duration,
+}
+
+impl Reaction for TimeoutReaction {
+    fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+        if now(world) >= self.deadline {
+            world.write_mutable(owner, true);
+        } else {
+            tracking.track_polled(Arc::new(Deadline(self.deadline)));
+        }
+    }
+
+    fn kind(&self) -> ReactionKind {
+        ReactionKind::Memo
+    }
+}
+
+/// Spawn a mutable, owned by `parent`, whose value is computed by a time-based reaction.
+#[track_caller]
+fn create_timed<T: PartialEq + Send + Sync + 'static, R: Reaction + Send + Sync + 'static>(
+    world: &mut World,
+    parent: Entity,
+    init: T,
+    mut reaction: R,
+) -> Signal<T> {
+    let mut scope = TrackingScope::new(world.change_tick());
+    let mutable = create_mutable(world, parent, init);
+    reaction.react(mutable.id(), world, &mut scope);
+    world.entity_mut(mutable.id()).insert((
+        ReactionCell::new(reaction),
+        scope,
+        Name::new(format!("Timed::<{}>", std::any::type_name::<T>())),
+    ));
+    mutable.signal()
+}
+
+/// Create a signal which follows `source`, but only changes once `source` has stopped
+/// changing for `secs` seconds. The delay is measured using the [`Time`] resource.
+#[track_caller]
+pub fn create_debounced<T: PartialEq + Clone + Send + Sync + 'static>(
+    world: &mut World,
+    parent: Entity,
+    source: Signal<T>,
+    secs: f32,
+) -> Signal<T> {
+    let value = source.get_clone(world);
+    create_timed(
+        world,
+        parent,
+        value.clone(),
+        RateLimitReaction {
+            source,
+            delay: Duration::from_secs_f32(secs),
+            mode: RateLimit::Debounce,
+            value,
+            pending: None,
+        },
+    )
+}
+
+/// Create a signal which follows `source`, but changes at most once every `secs` seconds.
+/// If `source` changes again during the interval, the signal takes on the latest value at the
+/// end of the interval. The interval is measured using the [`Time`] resource.
+#[track_caller]
+pub fn create_throttled<T: PartialEq + Clone + Send + Sync + 'static>(
+    world: &mut World,
+    parent: Entity,
+    source: Signal<T>,
+    secs: f32,
+) -> Signal<T> {
+    let value = source.get_clone(world);
+    create_timed(
+        world,
+        parent,
+        value.clone(),
+        RateLimitReaction {
+            source,
+            delay: Duration::from_secs_f32(secs),
+            mode: RateLimit::Throttle { last_emit: None },
+            value,
+            pending: None,
+        },
+    )
+}
+
+/// Create a signal which counts the number of whole periods of `secs` seconds which have
+/// elapsed since it was created, as measured using the [`Time`] resource.
+#[track_caller]
+pub fn create_interval(world: &mut World, parent: Entity, secs: f32) -> Signal<u64> {
+    assert!(secs > 0., "Interval period must be greater than zero");
+    let start = now(world);
+    create_timed(
+        world,
+        parent,
+        0,
+        IntervalReaction {
+            start,
+            period: Duration::from_secs_f32(secs),
+            count: 0,
+        },
+    )
+}
+
+/// Create a signal which is false until `secs` seconds have elapsed since it was created, and
+/// true thereafter, as measured using the [`Time`] resource.
+#[track_caller]
+pub fn create_timeout(world: &mut World, parent: Entity, secs: f32) -> Signal<bool> {
+    let deadline = now(world) + Duration::from_secs_f32(secs);
+    create_timed(world, parent, false, TimeoutReaction { deadline })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CreateMutable, SignalsPlugin};
+
+    fn advance(app: &mut App, secs: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(secs));
+        app.update();
+    }
+
+    #[test]
+    fn test_debounce_and_throttle() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin).init_resource::<Time>();
+        let world = app.world_mut();
+        let owner = world.spawn_empty().id();
+        let input = world.create_mutable(0);
+        let debounced = create_debounced(world, owner, input.signal(), 1.0);
+        let throttled = create_throttled(world, owner, input.signal(), 1.0);
+        app.update();
+
+        input.set(app.world_mut(), 1);
+        advance(&mut app, 0.5);
+        assert_eq!(debounced.get(app.world()), 0);
+        assert_eq!(throttled.get(app.world()), 1);
+
+        input.set(app.world_mut(), 2);
+        advance(&mut app, 0.25);
+        assert_eq!(throttled.get(app.world()), 1);
+        input.set(app.world_mut(), 3);
+        advance(&mut app, 0.25);
+        assert_eq!(throttled.get(app.world()), 1);
+        advance(&mut app, 0.5);
+        assert_eq!(debounced.get(app.world()), 0);
+        assert_eq!(throttled.get(app.world()), 3);
+
+        advance(&mut app, 0.5);
+        assert_eq!(debounced.get(app.world()), 3);
+    }
+
+    #[test]
+    fn test_interval_and_timeout() {
+        let mut app = App::new();
+        app.add_plugins(SignalsPlugin).init_resource::<Time>();
+        let world = app.world_mut();
+        let owner = world.spawn_empty().id();
+        let interval = create_interval(world, owner, 1.0);
+        let timeout = create_timeout(world, owner, 2.5);
+
+        advance(&mut app, 0.5);
+        assert_eq!(interval.get(app.world()), 0);
+        assert!(!timeout.get(app.world()));
+        advance(&mut app, 0.5);
+        assert_eq!(interval.get(app.world()), 1);
+        advance(&mut app, 2.0);
+        assert_eq!(interval.get(app.world()), 3);
+        assert!(timeout.get(app.world()));
+
+        // Timers are removed along with their owner.
+        let Signal::Mutable(cell) = interval else {
+            unreachable!();
+        };
+        app.world_mut().entity_mut(owner).despawn_recursive();
+        advance(&mut app, 1.0);
+        assert!(app.world().get_entity(cell.id()).is_err());
+    }
+}
@@ -1,15 +1,25 @@
-use std::sync::atomic::AtomicBool;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    sync::{atomic::AtomicBool, Arc, Mutex},
+};
 
 use bevy::{
     ecs::{
         component::{ComponentId, Tick},
+        schedule::{InternedScheduleLabel, ScheduleLabel},
         world::DeferredWorld,
     },
     prelude::*,
-    utils::HashSet,
+    utils::{HashSet, Instant},
 };
 
-use crate::ReactionCell;
+use crate::{
+    dependency_index::{DependencyIndex, Subscription},
+    divergence::{report_divergence, ReactionDivergenceLimit},
+    profiler::{ReactionProfiler, ReactionRun},
+    ReactionCell, ReactionKind,
+};
 
 /// A component that tracks the dependencies of a reactive task.
 #[derive(Component)]
@@ -20,6 +30,9 @@ pub struct TrackingScope {
     /// Set of resources that we are currently subscribed to.
     resource_deps: HashSet<ComponentId>,
 
+    /// Dependencies which can't be expressed as a component or resource, such as queries.
+    polled_deps: Vec<Arc<dyn PolledDependency>>,
+
     /// Allows a tracking scope to be explictly marked as changed for reasons other than
     /// a component or resource dependency mutation.
     changed: AtomicBool,
@@ -28,6 +41,19 @@ pub struct TrackingScope {
     /// beginning of the next inter-system interval, but not immediately.
     deferred_change: bool,
 
+    /// The schedule in which this scope reacts. If `None`, the scope reacts in the default
+    /// schedule used by [`SignalsPlugin`](crate::SignalsPlugin).
+    schedule: Option<InternedScheduleLabel>,
+
+    /// The entity which owns this scope, along with the queue used to notify the
+    /// dependency index when the scope is explicitly marked as changed. This is filled in
+    /// when the scope is inserted into the world.
+    notify: Option<(Entity, Arc<Mutex<Vec<Entity>>>)>,
+
+    /// Functions to call with the owning entity once the scope is inserted into the world,
+    /// for dependencies which need to know which reaction they belong to.
+    attachments: Vec<Attachment>,
+
     /// Engine tick used for determining if components have changed. This represents the
     /// time of the previous reaction.
     pub(crate) tick: Tick,
@@ -36,8 +62,66 @@ pub struct TrackingScope {
     /// TODO: This is a concept taken from Solid, but I don't actually use it anywhere.
     /// The envisioned use case is for effects that need to undo the changes of the previous action
     /// (like stopping a timer or unsubscibing to a listener) before performing the next action.
-    #[allow(clippy::type_complexity)]
-    pub(crate) cleanups: Vec<Box<dyn FnOnce(&mut DeferredWorld) + 'static + Sync + Send>>,
+    pub(crate) cleanups: Vec<Cleanup>,
+}
+
+/// A boxed cleanup function, see [`TrackingScope::add_cleanup`].
+pub(crate) type Cleanup = Box<dyn FnOnce(&mut DeferredWorld) + 'static + Sync + Send>;
+
+/// A function which is called with the entity that owns a scope, see [`TrackingScope::attach`].
+pub(crate) type Attachment = Box<dyn FnOnce(&mut DeferredWorld, Entity) + 'static + Sync + Send>;
+
+/// Cleanup functions which became due while the world could only be borrowed immutably, such
+/// as when a cached derived is recomputed during a read. These are run at the start of the
+/// next pass of [`run_reactions`].
+#[derive(Resource, Default)]
+pub(crate) struct RetiredCleanups(Mutex<Vec<Cleanup>>);
+
+impl RetiredCleanups {
+    /// Queue cleanup functions to be run later.
+    pub(crate) fn push(&self, cleanups: Vec<Cleanup>) {
+        if !cleanups.is_empty() {
+            self.0.lock().unwrap().extend(cleanups);
+        }
+    }
+}
+
+/// Run any cleanup functions in [`RetiredCleanups`].
+fn run_retired_cleanups(world: &mut World) {
+    let Some(mut retired) = world.get_resource_mut::<RetiredCleanups>() else {
+        return;
+    };
+    let cleanups = std::mem::take(retired.0.get_mut().unwrap());
+    let mut deferred = DeferredWorld::from(world);
+    for cleanup_fn in cleanups {
+        cleanup_fn(&mut deferred);
+    }
+}
+
+/// A dependency which can't be represented as a single component or resource, and which
+/// therefore needs to be checked for changes by the tracking scope itself.
+pub trait PolledDependency: Send + Sync {
+    /// Returns true if the dependency has changed since `last_run`.
+    fn changed(&self, world: &World, last_run: Tick, this_run: Tick) -> bool;
+
+    /// A name for the dependency, used in diagnostics such as the
+    /// [`ReactionProfiler`](crate::ReactionProfiler). Defaults to the type name.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// A dependency of a tracking scope which has changed since the scope last reacted.
+#[derive(Debug, Clone)]
+pub struct ChangedDependency {
+    /// The entity that holds the component, or `None` if the dependency is a resource.
+    pub entity: Option<Entity>,
+
+    /// The id of the component or resource.
+    pub component: ComponentId,
+
+    /// The type name of the component or resource.
+    pub type_name: String,
 }
 
 /// A resource which, if inserted, displays the view entities that have reacted this frame.
@@ -56,8 +140,12 @@ impl TrackingScope {
         Self {
             component_deps: HashSet::default(),
             resource_deps: HashSet::default(),
+            polled_deps: Vec::new(),
             changed: AtomicBool::new(false),
             deferred_change: false,
+            schedule: None,
+            notify: None,
+            attachments: Vec::new(),
             tick,
             cleanups: Vec::new(),
         }
@@ -97,10 +185,41 @@ impl TrackingScope {
         self.component_deps.insert((entity, component));
     }
 
+    /// Add a dependency which is checked for changes by calling [`PolledDependency::changed`].
+    pub fn track_polled(&mut self, dep: Arc<dyn PolledDependency>) {
+        self.polled_deps.push(dep);
+    }
+
+    /// The entity which owns this scope, if it is known. This is `None` until the scope has
+    /// been inserted into the world, or inherited the entity of the scope it replaces.
+    pub(crate) fn entity(&self) -> Option<Entity> {
+        self.notify.as_ref().map(|(entity, _)| *entity)
+    }
+
+    /// Adopt the owning entity of `other`, which is the scope that this one will replace.
+    pub(crate) fn inherit_entity(&mut self, other: &Self) {
+        self.notify = other.notify.clone();
+    }
+
+    /// Call `attachment` with the entity which owns this scope when the scope is inserted into
+    /// the world. This is for dependencies which are read before the entity is known.
+    pub(crate) fn attach(
+        &mut self,
+        attachment: impl FnOnce(&mut DeferredWorld, Entity) + 'static + Sync + Send,
+    ) {
+        self.attachments.push(Box::new(attachment));
+    }
+
     /// Mark the scope as changed for reasons other than a component or resource dependency.
     pub fn set_changed(&self) {
-        self.changed
-            .store(true, std::sync::atomic::Ordering::Relaxed);
+        let was_changed = self
+            .changed
+            .swap(true, std::sync::atomic::Ordering::Relaxed);
+        if !was_changed {
+            if let Some((entity, queue)) = &self.notify {
+                queue.lock().unwrap().push(*entity);
+            }
+        }
     }
 
     /// Set a flag that indicates that this tracking scope should be considered out of date at the
@@ -112,14 +231,92 @@ impl TrackingScope {
         self.deferred_change = true;
     }
 
+    /// Set the schedule in which this tracking scope reacts. By default, reactions run in the
+    /// `Update` schedule; this allows a reaction to run in a later phase instead, for example
+    /// after UI layout has been computed. Reactions will only be run in schedules which have
+    /// been registered with [`AddReactionSchedule::add_reaction_schedule`].
+    ///
+    /// [`AddReactionSchedule::add_reaction_schedule`]: crate::AddReactionSchedule::add_reaction_schedule
+    pub fn set_schedule(&mut self, schedule: impl ScheduleLabel) {
+        self.schedule = Some(schedule.intern());
+    }
+
+    /// Returns true if this scope has requested to be re-run at the next inter-system interval.
+    pub(crate) fn has_deferred_change(&self) -> bool {
+        self.deferred_change
+    }
+
+    /// Add all of the dependencies of another scope to this one. This is used when reading
+    /// a cached value, so that the reader depends on everything the cached value depends on.
+    /// If this scope has no schedule of its own, it also adopts the other scope's schedule.
+    pub(crate) fn copy_deps(&mut self, other: &Self) {
+        self.component_deps
+            .extend(other.component_deps.iter().copied());
+        self.resource_deps
+            .extend(other.resource_deps.iter().copied());
+        self.polled_deps.extend(other.polled_deps.iter().cloned());
+        self.deferred_change |= other.deferred_change;
+        self.schedule = self.schedule.or(other.schedule);
+    }
+
     /// Returns true if any of the dependencies of this scope have been updated since
     /// the previous reaction.
     pub fn dependencies_changed(&self, world: &World, tick: Tick) -> bool {
         self.components_changed(world, tick)
             || self.resources_changed(world, tick)
+            || self.polled_changed(world, tick)
             || self.changed.load(std::sync::atomic::Ordering::Relaxed)
     }
 
+    /// Returns the component and resource dependencies of this scope which have been updated
+    /// since the previous reaction.
+    pub(crate) fn changed_dependencies(&self, world: &World, tick: Tick) -> Vec<ChangedDependency> {
+        let type_name = |id: ComponentId| {
+            world
+                .components()
+                .get_info(id)
+                .map_or_else(|| format!("{:?}", id), |info| info.name().to_string())
+        };
+        let components = self.component_deps.iter().filter(|(e, c)| {
+            world.get_entity(*e).is_ok_and(|e| {
+                e.get_change_ticks_by_id(*c)
+                    .is_some_and(|ct| ct.is_changed(self.tick, tick))
+            })
+        });
+        let resources = self.resource_deps.iter().filter(|c| {
+            world
+                .get_resource_change_ticks_by_id(**c)
+                .is_some_and(|ct| ct.is_changed(self.tick, tick))
+        });
+        components
+            .map(|(e, c)| ChangedDependency {
+                entity: Some(*e),
+                component: *c,
+                type_name: type_name(*c),
+            })
+            .chain(resources.map(|c| ChangedDependency {
+                entity: None,
+                component: *c,
+                type_name: type_name(*c),
+            }))
+            .collect()
+    }
+
+    /// The component dependencies of this scope.
+    pub(crate) fn component_dependencies(&self) -> impl Iterator<Item = &(Entity, ComponentId)> {
+        self.component_deps.iter()
+    }
+
+    /// The resource dependencies of this scope.
+    pub(crate) fn resource_dependencies(&self) -> impl Iterator<Item = &ComponentId> {
+        self.resource_deps.iter()
+    }
+
+    /// The polled dependencies of this scope.
+    pub(crate) fn polled_dependencies(&self) -> impl Iterator<Item = &Arc<dyn PolledDependency>> {
+        self.polled_deps.iter()
+    }
+
     fn components_changed(&self, world: &World, tick: Tick) -> bool {
         self.component_deps.iter().any(|(e, c)| {
             world.get_entity(*e).map_or(false, |e| {
@@ -139,19 +336,70 @@ impl TrackingScope {
         })
     }
 
+    fn polled_changed(&self, world: &World, tick: Tick) -> bool {
+        self.polled_deps
+            .iter()
+            .any(|dep| dep.changed(world, self.tick, tick))
+    }
+
     /// Take the dependencies from another scope. Typically the other scope is a temporary
     /// scope that is used to compute the next set of dependencies.
     pub fn take_deps(&mut self, other: &mut Self) {
         self.component_deps = std::mem::take(&mut other.component_deps);
         self.resource_deps = std::mem::take(&mut other.resource_deps);
+        self.polled_deps = std::mem::take(&mut other.polled_deps);
         self.cleanups = std::mem::take(&mut other.cleanups);
+        self.deferred_change = other.deferred_change;
+        self.schedule = other.schedule;
+        self.changed.store(
+            other.changed.load(std::sync::atomic::Ordering::Relaxed),
+            std::sync::atomic::Ordering::Relaxed,
+        );
+    }
+
+    /// Returns the set of keys that this scope should be subscribed to in the
+    /// [`DependencyIndex`].
+    pub(crate) fn subscription(&self) -> Subscription {
+        Subscription {
+            components: self.component_deps.iter().copied().collect(),
+            resources: self.resource_deps.iter().copied().collect(),
+            polled: !self.polled_deps.is_empty(),
+            deferred: self.deferred_change,
+            changed: self.changed.load(std::sync::atomic::Ordering::Relaxed),
+            schedule: self.schedule,
+            ..default()
+        }
     }
 }
 
-/// Component hook which runs the cleanups when a tracking scope is despawned.
-pub(crate) fn cleanup_tracking_scopes(world: &mut World) {
+/// Component hooks which keep the [`DependencyIndex`] up to date as tracking scopes are
+/// inserted and replaced, and which run the cleanups when a tracking scope is despawned.
+pub(crate) fn register_tracking_scope_hooks(world: &mut World) {
     world
         .register_component_hooks::<TrackingScope>()
+        .on_insert(|mut world, entity, _component| {
+            let Some(queue) = world
+                .get_resource::<DependencyIndex>()
+                .map(|index| index.flagged.clone())
+            else {
+                return;
+            };
+            let mut scope = world.get_mut::<TrackingScope>(entity).unwrap();
+            scope.notify = Some((entity, queue));
+            let attachments = std::mem::take(&mut scope.attachments);
+            let subscription = scope.subscription();
+            world
+                .resource_mut::<DependencyIndex>()
+                .subscribe(entity, subscription, true);
+            for attachment in attachments {
+                attachment(&mut world, entity);
+            }
+        })
+        .on_replace(|mut world, entity, _component| {
+            if let Some(mut index) = world.get_resource_mut::<DependencyIndex>() {
+                index.unsubscribe(entity);
+            }
+        })
         .on_remove(|mut world, entity, _component| {
             let mut scope = world.get_mut::<TrackingScope>(entity).unwrap();
             let mut cleanups = std::mem::take(&mut scope.cleanups);
@@ -161,86 +409,235 @@ pub(crate) fn cleanup_tracking_scopes(world: &mut World) {
         });
 }
 
-fn run_cleanups(world: &mut World, changed: &[Entity]) {
+fn run_cleanups(world: &mut World, scope_entity: Entity) {
     let mut deferred = DeferredWorld::from(world);
-    for scope_entity in changed.iter() {
-        let Some(mut scope) = deferred.get_mut::<TrackingScope>(*scope_entity) else {
-            continue;
-        };
-        let mut cleanups = std::mem::take(&mut scope.cleanups);
-        for cleanup_fn in cleanups.drain(..) {
-            cleanup_fn(&mut deferred);
+    let Some(mut scope) = deferred.get_mut::<TrackingScope>(scope_entity) else {
+        return;
+    };
+    let mut cleanups = std::mem::take(&mut scope.cleanups);
+    for cleanup_fn in cleanups.drain(..) {
+        cleanup_fn(&mut deferred);
+    }
+}
+
+/// Returns true if the given entity has a reaction whose dependencies have changed.
+fn reaction_changed(world: &World, entity: Entity, tick: Tick, include_deferred: bool) -> bool {
+    let Ok(entity) = world.get_entity(entity) else {
+        return false;
+    };
+    if !entity.contains::<ReactionCell>() {
+        return false;
+    }
+    entity.get::<TrackingScope>().is_some_and(|scope| {
+        scope.dependencies_changed(world, tick) || (include_deferred && scope.deferred_change)
+    })
+}
+
+/// Record the dependencies which caused a reaction to run, for diagnosing a failure to converge.
+fn record_suspect(
+    world: &World,
+    entity: Entity,
+    suspects: &mut Vec<(Entity, Vec<ChangedDependency>)>,
+) {
+    let Some(scope) = world.get::<TrackingScope>(entity) else {
+        return;
+    };
+    let changed = scope.changed_dependencies(world, world.read_change_tick());
+    let deps = match suspects.iter_mut().find(|(e, _)| *e == entity) {
+        Some((_, deps)) => deps,
+        None => {
+            suspects.push((entity, Vec::new()));
+            &mut suspects.last_mut().unwrap().1
+        }
+    };
+    for dep in changed {
+        if !deps
+            .iter()
+            .any(|d| d.entity == dep.entity && d.component == dep.component)
+        {
+            deps.push(dep);
         }
     }
 }
 
-const MAX_DIVERGENCE_CT: usize = 32;
+/// Priority queue of reactions waiting to run, ordered by kind and then by dependency depth.
+#[derive(Default)]
+struct ReactionQueue {
+    heap: BinaryHeap<Reverse<(ReactionKind, u32, Entity)>>,
+    queued: HashSet<Entity>,
+}
+
+impl ReactionQueue {
+    fn push(&mut self, world: &World, entity: Entity) {
+        if !self.queued.insert(entity) {
+            return;
+        }
+        let Some(kind) = world
+            .get::<ReactionCell>(entity)
+            .map(|cell| cell.0.lock().unwrap().kind())
+        else {
+            return;
+        };
+        let depth = world.resource::<DependencyIndex>().depth(entity);
+        self.heap.push(Reverse((kind, depth, entity)));
+    }
+
+    fn pop(&mut self) -> Option<Entity> {
+        self.heap.pop().map(|Reverse((_, _, entity))| entity)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
 
 /// Run reactions whose dependencies have changed. This uses a "run to convergence" strategy:
 /// running a reaction may trigger other reactions, so we loop until there are no more reactions
 /// left to run. However, to avoid an infinite loop we require that the reactions eventually
 /// reach a quiescent state. We count the number of "divergences" (cycles where the number
-/// of reactions didn't decrease) and impose a strict limit on the number of such cycles.
+/// of reactions didn't decrease) and impose a limit on the number of such cycles, given by the
+/// [`ReactionDivergenceLimit`] resource. If the limit is exceeded, a [`ReactionDivergence`]
+/// event is sent and the remaining reactions are left for the next run.
+///
+/// [`ReactionDivergence`]: crate::ReactionDivergence
+///
+/// Rather than checking every tracking scope, we use the [`DependencyIndex`] to find the
+/// scopes which subscribe to a dependency that has changed.
+///
+/// Within each pass, reactions are run in order of [`ReactionKind`] and then dependency depth,
+/// so that memos are updated before the effects which read them. Each reaction runs at most
+/// once per pass; when a reaction finishes, the reactions which depend on its output are added
+/// to the current pass.
 pub(crate) fn run_reactions(world: &mut World) {
+    run_reactions_in_schedule(world, None);
+}
+
+/// Run reactions which have opted in to the given schedule. See [`run_reactions`].
+pub(crate) fn run_reactions_in_schedule(
+    world: &mut World,
+    schedule: Option<InternedScheduleLabel>,
+) {
     let is_tracing = world.get_resource_mut::<TrackingScopeTracing>().is_some();
+    let is_profiling = world.contains_resource::<ReactionProfiler>();
+    if is_profiling && schedule.is_none() {
+        world.resource_mut::<ReactionProfiler>().begin_frame();
+    }
     let mut all_reactions: Vec<Entity> = Vec::new();
     let mut iteration_ct: usize = 0;
     let mut divergence_ct: usize = 0;
     let mut prev_change_ct: usize = 0;
+    let max_divergence_ct = world
+        .get_resource::<ReactionDivergenceLimit>()
+        .copied()
+        .unwrap_or_default()
+        .0;
+
+    // Once we are halfway to the divergence limit, we start recording which reactions are
+    // running and why, so that we can report them if the limit is reached.
+    let mut suspects: Vec<(Entity, Vec<ChangedDependency>)> = Vec::new();
 
     loop {
-        // If this is the first iteration, use the world's change tick, otherwise bump the
-        // tick and use that.
-        let this_run = if iteration_ct > 0 {
-            world.increment_change_tick()
-        } else {
-            world.change_tick()
-        };
+        run_retired_cleanups(world);
+        let this_run = world.change_tick();
 
         // Find all tracking scopes that have changes.
-        let mut scopes = world.query::<(Entity, &mut TrackingScope, &ReactionCell)>();
-        let mut changed: Vec<Entity> = Vec::with_capacity(64);
-        for (entity, scope, _) in scopes.iter(world) {
-            // We only test the 'always changed' flag the first time through the loop; otherwise
-            // we would never get to convergence.
-            if scope.dependencies_changed(world, this_run)
-                || (iteration_ct == 0 && scope.deferred_change)
-            {
-                changed.push(entity);
+        // We only test the 'always changed' flag the first time through the loop; otherwise
+        // we would never get to convergence.
+        let include_deferred = iteration_ct == 0;
+        let candidates = world.resource_scope(|world, mut index: Mut<DependencyIndex>| {
+            index.candidates(world, this_run, schedule, include_deferred)
+        });
+        let mut queue = ReactionQueue::default();
+        for entity in candidates {
+            if reaction_changed(world, entity, this_run, include_deferred) {
+                queue.push(world, entity);
             }
         }
 
         // Quit if there are no changes.
-        if changed.is_empty() {
+        if queue.is_empty() {
             break;
         }
 
-        // In debug mode, record the changed reactions in a resource.
-        if is_tracing {
-            all_reactions.extend(changed.clone());
-        }
+        let mut changed: Vec<Entity> = Vec::with_capacity(queue.heap.len());
+        while let Some(scope_entity) = queue.pop() {
+            // The entity may have been despawned by a previous reaction.
+            let Some(inner) = world
+                .get::<ReactionCell>(scope_entity)
+                .map(|cell| cell.0.clone())
+            else {
+                continue;
+            };
 
-        // Run any registered cleanup functions.
-        run_cleanups(world, &changed);
+            if divergence_ct * 2 >= max_divergence_ct {
+                record_suspect(world, scope_entity, &mut suspects);
+            }
+
+            // Run any registered cleanup functions.
+            run_cleanups(world, scope_entity);
+
+            // Give each reaction its own tick, so that changes made by a reaction are visible
+            // to every reaction that runs after it, but not to the reaction itself.
+            world.increment_change_tick();
+            let tick = world.change_tick();
+
+            // When profiling, record why the reaction is running.
+            let causes = match is_profiling {
+                true => world
+                    .get::<TrackingScope>(scope_entity)
+                    .map(|scope| scope.changed_dependencies(world, tick))
+                    .unwrap_or_default(),
+                false => Vec::new(),
+            };
+            let start = is_profiling.then(Instant::now);
 
-        // Run reactions
-        for scope_entity in changed.iter() {
             // Run the reaction
-            if world.get_entity(*scope_entity).is_err() {
-                continue;
+            let mut next_scope = TrackingScope::new(tick);
+            if let Some(scope) = world.get::<TrackingScope>(scope_entity) {
+                next_scope.inherit_entity(scope);
+            }
+            inner
+                .lock()
+                .unwrap()
+                .react(scope_entity, world, &mut next_scope);
+            changed.push(scope_entity);
+
+            if let Some(start) = start {
+                world
+                    .resource_mut::<ReactionProfiler>()
+                    .record(ReactionRun {
+                        entity: scope_entity,
+                        duration: start.elapsed(),
+                        iteration: iteration_ct,
+                        causes,
+                    });
             }
-            let Some(cell) = world.entity(*scope_entity).get::<ReactionCell>() else {
-                continue;
-            };
-            let mut next_scope = TrackingScope::new(this_run);
-            let inner = cell.0.clone();
-            let mut lock = inner.lock().unwrap();
-            lock.react(*scope_entity, world, &mut next_scope);
 
             // Replace deps and cleanups in the current scope with the next scope.
-            let (_, mut scope, _) = scopes.get_mut(world, *scope_entity).unwrap();
+            let Some(mut scope) = world.get_mut::<TrackingScope>(scope_entity) else {
+                continue;
+            };
             scope.take_deps(&mut next_scope);
-            scope.tick = this_run;
+            scope.tick = tick;
+            let subscription = scope.subscription();
+            let mut index = world.resource_mut::<DependencyIndex>();
+            index.subscribe(scope_entity, subscription, false);
+
+            // Schedule any reactions which read the output of this one.
+            let downstream: Vec<Entity> = index
+                .subscribers(scope_entity)
+                .filter(|entity| index.schedule(*entity) == schedule)
+                .collect();
+            for entity in downstream {
+                if reaction_changed(world, entity, tick, false) {
+                    queue.push(world, entity);
+                }
+            }
+        }
+
+        // In debug mode, record the changed reactions in a resource.
+        if is_tracing {
+            all_reactions.extend(changed.iter().copied());
         }
 
         // Check for divergence.
@@ -248,26 +645,145 @@ pub(crate) fn run_reactions(world: &mut World) {
         let change_ct = changed.len();
         if change_ct >= prev_change_ct {
             divergence_ct += 1;
-            if divergence_ct > MAX_DIVERGENCE_CT {
-                panic!("Reactions failed to converge, num changes: {}", change_ct);
+            if divergence_ct > max_divergence_ct {
+                report_divergence(world, suspects, iteration_ct);
+                break;
             }
         }
         prev_change_ct = change_ct;
     }
 
-    // Record the changed entities for diagnostic purposes.
+    if is_profiling {
+        world.resource_mut::<ReactionProfiler>().iterations += iteration_ct;
+    }
+
+    // Record the changed entities for diagnostic purposes. Passes in additional schedules
+    // add to the list for the current frame.
     if let Some(mut tracing) = world.get_resource_mut::<TrackingScopeTracing>() {
-        std::mem::swap(&mut tracing.0, &mut all_reactions);
+        if schedule.is_none() {
+            std::mem::swap(&mut tracing.0, &mut all_reactions);
+        } else {
+            tracing.0.extend(all_reactions);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
     use super::*;
+    use crate::{
+        create_mutable, AddReactionSchedule, Mutable, Rcx, Reaction, ReactionDivergence,
+        WriteMutable,
+    };
 
     #[derive(Resource, Default)]
     struct TestResource(bool);
 
+    #[derive(Resource, Default)]
+    struct OtherResource(bool);
+
+    /// Reaction which subscribes to a resource and counts how many times it has run.
+    struct CountingReaction<R: Resource> {
+        count: Arc<AtomicUsize>,
+        marker: std::marker::PhantomData<R>,
+    }
+
+    impl<R: Resource> Reaction for CountingReaction<R> {
+        fn react(&mut self, _owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+            tracking.track_resource::<R>(world);
+            self.count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Memo which doubles its input, storing the result in a mutable on the owner entity.
+    struct DoubleMemo {
+        input: Mutable<i32>,
+    }
+
+    impl Reaction for DoubleMemo {
+        fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+            let value = self.input.get(&Rcx::new(world, owner, tracking)) * 2;
+            world.write_mutable(owner, value);
+        }
+
+        fn kind(&self) -> ReactionKind {
+            ReactionKind::Memo
+        }
+    }
+
+    #[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    struct LatePhase;
+
+    /// Reaction which opts in to running in the `LatePhase` schedule.
+    struct LateReaction(Arc<AtomicUsize>);
+
+    impl Reaction for LateReaction {
+        fn react(&mut self, _owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+            tracking.track_resource::<TestResource>(world);
+            tracking.set_schedule(LatePhase);
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[derive(Resource, Default)]
+    struct Observed(Vec<(i32, i32)>);
+
+    /// Effect which records the values of a mutable and its doubled memo.
+    struct ObserveEffect {
+        input: Mutable<i32>,
+        doubled: Mutable<i32>,
+    }
+
+    impl Reaction for ObserveEffect {
+        fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+            let rcx = Rcx::new(world, owner, tracking);
+            let pair = (self.input.get(&rcx), self.doubled.get(&rcx));
+            world.resource_mut::<Observed>().0.push(pair);
+        }
+    }
+
+    /// Reaction which sets one mutable to the value of another, plus one.
+    struct Increment {
+        from: Mutable<i32>,
+        to: Mutable<i32>,
+    }
+
+    impl Reaction for Increment {
+        fn react(&mut self, owner: Entity, world: &mut World, tracking: &mut TrackingScope) {
+            let value = self.from.get(&Rcx::new(world, owner, tracking));
+            self.to.set(world, value + 1);
+        }
+    }
+
+    /// Run the initial reaction and attach it to the owner entity.
+    fn start_reaction<R: Reaction + Send + Sync + 'static>(
+        world: &mut World,
+        owner: Entity,
+        mut reaction: R,
+    ) {
+        let mut scope = TrackingScope::new(world.change_tick());
+        reaction.react(owner, world, &mut scope);
+        world
+            .entity_mut(owner)
+            .insert((scope, ReactionCell::new(reaction)));
+    }
+
+    fn spawn_counter<R: Resource>(world: &mut World) -> Arc<AtomicUsize> {
+        let count = Arc::new(AtomicUsize::new(0));
+        let owner = world.spawn_empty().id();
+        start_reaction(
+            world,
+            owner,
+            CountingReaction::<R> {
+                count: count.clone(),
+                marker: std::marker::PhantomData,
+            },
+        );
+        count
+    }
+
     #[test]
     fn test_resource_deps_changed() {
         let mut world = World::default();
@@ -296,4 +812,161 @@ mod tests {
         let tick = world.change_tick();
         assert!(scope.dependencies_changed(&world, tick));
     }
+
+    #[test]
+    fn test_dependency_index() {
+        let mut world = World::default();
+        register_tracking_scope_hooks(&mut world);
+        world.init_resource::<DependencyIndex>();
+        world.init_resource::<TestResource>();
+        world.init_resource::<OtherResource>();
+        world.increment_change_tick();
+
+        let test_count = spawn_counter::<TestResource>(&mut world);
+        let other_count = spawn_counter::<OtherResource>(&mut world);
+        assert_eq!(test_count.load(Ordering::Relaxed), 1);
+        assert_eq!(other_count.load(Ordering::Relaxed), 1);
+
+        // Nothing changed since the reactions were created.
+        world.increment_change_tick();
+        run_reactions(&mut world);
+        assert_eq!(test_count.load(Ordering::Relaxed), 1);
+        assert_eq!(other_count.load(Ordering::Relaxed), 1);
+
+        // Only the subscriber of the changed resource should react.
+        world.increment_change_tick();
+        world.resource_mut::<TestResource>().0 = true;
+        world.increment_change_tick();
+        run_reactions(&mut world);
+        assert_eq!(test_count.load(Ordering::Relaxed), 2);
+        assert_eq!(other_count.load(Ordering::Relaxed), 1);
+
+        // Despawned scopes no longer react.
+        world.increment_change_tick();
+        world.resource_mut::<OtherResource>().0 = true;
+        let scope = world
+            .query_filtered::<Entity, With<TrackingScope>>()
+            .iter(&world)
+            .max()
+            .unwrap();
+        world.despawn(scope);
+        world.increment_change_tick();
+        run_reactions(&mut world);
+        assert_eq!(other_count.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_memo_runs_before_effect() {
+        let mut world = World::default();
+        register_tracking_scope_hooks(&mut world);
+        world.init_resource::<DependencyIndex>();
+        world.init_resource::<Observed>();
+
+        // Spawn the effect entity first, so that it would come first in entity order.
+        let effect_owner = world.spawn_empty().id();
+        let root = world.spawn_empty().id();
+        let input = create_mutable(&mut world, root, 1);
+        let doubled = create_mutable(&mut world, root, 0);
+        start_reaction(&mut world, doubled.id(), DoubleMemo { input });
+        start_reaction(&mut world, effect_owner, ObserveEffect { input, doubled });
+        assert_eq!(world.resource::<Observed>().0, vec![(1, 2)]);
+
+        // The effect should run once, and never see a stale value of the memo.
+        world.increment_change_tick();
+        input.set(&mut world, 2);
+        run_reactions(&mut world);
+        assert_eq!(world.resource::<Observed>().0, vec![(1, 2), (2, 4)]);
+
+        // Nothing further to do.
+        world.increment_change_tick();
+        run_reactions(&mut world);
+        assert_eq!(world.resource::<Observed>().0, vec![(1, 2), (2, 4)]);
+    }
+
+    #[test]
+    fn test_reaction_schedule() {
+        let mut world = World::default();
+        register_tracking_scope_hooks(&mut world);
+        world.init_resource::<DependencyIndex>();
+        world.init_resource::<TestResource>();
+        world.increment_change_tick();
+
+        let early_count = spawn_counter::<TestResource>(&mut world);
+        let late_count = Arc::new(AtomicUsize::new(0));
+        let owner = world.spawn_empty().id();
+        start_reaction(&mut world, owner, LateReaction(late_count.clone()));
+
+        world.increment_change_tick();
+        world.resource_mut::<TestResource>().0 = true;
+        world.increment_change_tick();
+
+        // The default pass only runs reactions which haven't opted in to another schedule.
+        run_reactions(&mut world);
+        assert_eq!(early_count.load(Ordering::Relaxed), 2);
+        assert_eq!(late_count.load(Ordering::Relaxed), 1);
+
+        // The late pass picks up the same change.
+        run_reactions_in_schedule(&mut world, Some(LatePhase.intern()));
+        assert_eq!(early_count.load(Ordering::Relaxed), 2);
+        assert_eq!(late_count.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_schedule_propagation() {
+        // Readers of a cached value adopt its schedule, unless they have their own.
+        let mut cached = TrackingScope::new(Tick::new(0));
+        cached.set_schedule(LatePhase);
+        let mut reader = TrackingScope::new(Tick::new(0));
+        reader.copy_deps(&cached);
+        assert_eq!(reader.schedule, Some(LatePhase.intern()));
+        let mut reader = TrackingScope::new(Tick::new(0));
+        reader.set_schedule(Update);
+        reader.copy_deps(&cached);
+        assert_eq!(reader.schedule, Some(Update.intern()));
+
+        // Adding the same reaction schedule twice only adds one pass.
+        let mut app = App::new();
+        app.add_plugins(crate::SignalsPlugin)
+            .add_reaction_schedule(LatePhase)
+            .add_reaction_schedule(LatePhase);
+        assert_eq!(app.get_schedule(LatePhase).unwrap().systems_len(), 1);
+    }
+
+    #[test]
+    fn test_divergence() {
+        let mut world = World::default();
+        register_tracking_scope_hooks(&mut world);
+        world.init_resource::<DependencyIndex>();
+        world.insert_resource(ReactionDivergenceLimit(4));
+        world.init_resource::<Events<ReactionDivergence>>();
+
+        // Two reactions which keep triggering each other.
+        let root = world.spawn_empty().id();
+        let a = create_mutable(&mut world, root, 0);
+        let b = create_mutable(&mut world, root, 0);
+        let first = world.spawn(Name::new("First")).id();
+        start_reaction(&mut world, first, Increment { from: a, to: b });
+        let second = world.spawn_empty().id();
+        start_reaction(&mut world, second, Increment { from: b, to: a });
+
+        // Should give up rather than panicking.
+        world.increment_change_tick();
+        a.set(&mut world, 10);
+        run_reactions(&mut world);
+        let events: Vec<_> = world
+            .resource::<Events<ReactionDivergence>>()
+            .iter_current_update_events()
+            .cloned()
+            .collect();
+        assert_eq!(events.len(), 1);
+        let scopes = &events[0].scopes;
+        assert_eq!(scopes.len(), 2);
+        let first_scope = scopes.iter().find(|s| s.entity == first).unwrap();
+        assert_eq!(first_scope.name, Some(Name::new("First")));
+        assert_eq!(first_scope.dependencies.len(), 1);
+        assert_eq!(first_scope.dependencies[0].entity, Some(a.id()));
+        let second_scope = scopes.iter().find(|s| s.entity == second).unwrap();
+        assert_eq!(second_scope.name, None);
+        assert_eq!(second_scope.dependencies[0].entity, Some(b.id()));
+    }
 }